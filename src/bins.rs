@@ -0,0 +1,175 @@
+// Length histogram bins, shared by the table sparklines and the HTML report
+
+use human_repr::HumanCount;
+
+use crate::UploadedFile;
+
+pub const DEFAULT_COUNT: usize = 30;
+
+// bin i spans edges[i]..edges[i + 1]; with an open end the last bin also takes everything longer
+#[derive(Clone, PartialEq)]
+pub struct LengthBins {
+    edges: Vec<usize>,
+    open_end: bool,
+    pub log: bool,
+}
+
+impl LengthBins {
+    // count bins of binsize bp from 0, as before auto binning
+    pub fn fixed(binsize: usize, count: usize) -> Self {
+        let binsize = binsize.max(1);
+        LengthBins { edges: (0..=count.max(1)).map(|i| i * binsize).collect(), open_end: true, log: false }
+    }
+
+    // equal bins of a round size covering min..=max
+    pub fn auto(min: usize, max: usize, count: usize) -> Self {
+        let count = count.max(1);
+        let mut size = nice_size((max - min.min(max)).div_ceil(count).max(1));
+        let mut start = min / size * size;
+        while start + count * size <= max {
+            size = nice_size(size + 1);
+            start = min / size * size;
+        }
+        LengthBins { edges: (0..=count).map(|i| start + i * size).collect(), open_end: false, log: false }
+    }
+
+    // geometric bins covering min..=max; neighbouring edges that round to the same length are merged
+    pub fn log(min: usize, max: usize, count: usize) -> Self {
+        let count = count.max(1);
+        let (lo, hi) = (min.max(1) as f64, (max.max(min) + 1) as f64);
+        let mut edges: Vec<usize> = (0..=count)
+            .map(|i| (lo * (hi / lo).powf(i as f64 / count as f64)).round() as usize)
+            .collect();
+        edges.dedup();
+        if edges.len() < 2 {
+            edges.push(edges[0] + 1);
+        }
+        LengthBins { edges, open_end: false, log: true }
+    }
+
+    // mode is "fixed", "auto" or "log"; auto and log bins span the lengths of all files, so rows stay comparable
+    pub fn for_files(mode: &str, binsize: usize, count: usize, files: &[UploadedFile]) -> Self {
+        let lengths = files.iter().flat_map(|f| f.l_vector.iter()).map(|&l| l as usize);
+        let (min, max) = lengths.fold((usize::MAX, 0), |(lo, hi), l| (lo.min(l), hi.max(l)));
+        match mode {
+            _ if max == 0 => Self::fixed(binsize, count),
+            "auto" => Self::auto(min, max, count),
+            "log" => Self::log(min, max, count),
+            _ => Self::fixed(binsize, count),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len() - 1
+    }
+
+    pub fn start(&self, bin: usize) -> usize {
+        self.edges[bin]
+    }
+
+    pub fn end(&self, bin: usize) -> usize {
+        self.edges[bin + 1]
+    }
+
+    pub fn index(&self, length: usize) -> usize {
+        let bin = self.edges.partition_point(|&e| e <= length).saturating_sub(1);
+        bin.min(self.len() - 1)
+    }
+
+    // reads and bases per bin
+    pub fn count(&self, l_vector: &[i64]) -> (Vec<u64>, Vec<usize>) {
+        let mut reads = vec![0; self.len()];
+        let mut bases = vec![0; self.len()];
+        for &l in l_vector {
+            let bin = self.index(l as usize);
+            reads[bin] += 1;
+            bases[bin] += l as usize;
+        }
+        (reads, bases)
+    }
+
+    pub fn label(&self, bin: usize) -> String {
+        if self.open_end && bin == self.len() - 1 {
+            format!(">{}", self.edges[bin].human_count_bare())
+        } else {
+            format!("{}-{}", self.edges[bin].human_count_bare(), self.edges[bin + 1].human_count_bare())
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let end = self.edges[self.len()].human_count_bare();
+        let more = if self.open_end { "+" } else { "" };
+        format!("{} bins, {}-{}{} bp", self.len(), self.edges[0].human_count_bare(), end, more)
+    }
+}
+
+// smallest of 1, 2, 2.5, 5 x 10^k at least n
+fn nice_size(n: usize) -> usize {
+    let mut magnitude = 1;
+    loop {
+        for step in [10, 20, 25, 50] {
+            let size = step * magnitude / 10;
+            if size >= n && size > 0 {
+                return size;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+// Qscore bins of width Q units from Q0; the last bin also takes everything higher
+#[derive(Clone, PartialEq)]
+pub struct QualityBins {
+    pub width: usize,
+    pub count: usize,
+}
+
+impl QualityBins {
+    // bins of width up to max_q, as before auto binning with width 2 and max 60
+    pub fn fixed(width: usize, max_q: usize) -> Self {
+        let width = width.max(1);
+        QualityBins { width, count: max_q.div_ceil(width).max(1) }
+    }
+
+    // at most DEFAULT_COUNT bins reaching the highest Q of all files
+    pub fn for_files(mode: &str, width: usize, max_q: usize, files: &[UploadedFile]) -> Self {
+        if mode == "fixed" {
+            return Self::fixed(width, max_q);
+        }
+        let highest = files
+            .iter()
+            .flat_map(|f| f.q_vector.iter().copied().chain(f.q_hash.keys().map(|q| q.saturating_sub(33))))
+            .max()
+            .unwrap_or(0) as usize;
+        let top = highest + 1;
+        Self::fixed(top.div_ceil(DEFAULT_COUNT).max(1), top.max(10))
+    }
+
+    pub fn index(&self, q: usize) -> usize {
+        (q / self.width).min(self.count - 1)
+    }
+
+    pub fn max(&self) -> usize {
+        self.count * self.width
+    }
+
+    pub fn label(&self, bin: usize) -> String {
+        let start = bin * self.width;
+        if bin == self.count - 1 {
+            format!("≥{}", start)
+        } else if self.width == 1 {
+            format!("{}", start)
+        } else {
+            format!("{}-{}", start, start + self.width)
+        }
+    }
+
+    // position of a Qscore along the axis, in percent
+    pub fn percent(&self, q: f64) -> f64 {
+        (q / self.max() as f64 * 100.0).clamp(0.0, 100.0)
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} bins of Q{}, Q0-{}+", self.count, self.width, self.max() - self.width)
+    }
+}
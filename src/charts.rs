@@ -0,0 +1,587 @@
+// SVG charts with axes, ticks and hover readouts; the same markup is drawn in the app and
+// embedded in the HTML report, where <title> elements give the readouts without any JS
+
+use std::fmt::Write;
+
+use human_repr::HumanCount;
+
+use crate::bins::LengthBins;
+use crate::density::Density;
+use crate::positions::{Positions, BASES};
+
+pub const WIDTH: f64 = 640.0;
+pub const HEIGHT: f64 = 260.0;
+pub const LEFT: f64 = 64.0;
+pub const RIGHT: f64 = 16.0;
+pub const TOP: f64 = 28.0;
+pub const BOTTOM: f64 = 42.0;
+
+const COLOURS: [&str; 8] = ["#2E86C1", "#f37736", "#28B463", "#C0392B", "#8E44AD", "#D4AC0D", "#17A589", "#7F8C8D"];
+const YIELD_POINTS: usize = 120;
+
+// a bar spanning x0..x1, or a line point at x0 when x0 == x1
+#[derive(Clone, PartialEq)]
+pub struct Mark {
+    pub x0: f64,
+    pub x1: f64,
+    pub y: f64,
+    pub tip: String,
+}
+
+impl Mark {
+    fn mid(&self) -> f64 {
+        (self.x0 + self.x1) / 2.0
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub marks: Vec<Mark>,
+}
+
+// several series are overlaid with a legend
+#[derive(Clone, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_log: bool,
+    pub x_range: (f64, f64),
+    pub line: bool, // marks are joined by a line through their middle instead of drawn as bars
+    pub series: Vec<Series>,
+    pub markers: Vec<(f64, String)>, // labelled vertical lines at data x
+}
+
+impl Chart {
+    pub fn plot_width() -> f64 {
+        WIDTH - LEFT - RIGHT
+    }
+
+    pub fn plot_height() -> f64 {
+        HEIGHT - TOP - BOTTOM
+    }
+
+    fn x_fraction(&self, x: f64) -> f64 {
+        let (lo, hi) = self.x_range;
+        let t = if self.x_log { (x.max(lo).ln() - lo.ln()) / (hi.ln() - lo.ln()) } else { (x - lo) / (hi - lo) };
+        if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    fn x_px(&self, x: f64) -> f64 {
+        LEFT + self.x_fraction(x) * Self::plot_width()
+    }
+
+    // data x at a horizontal offset into the plot area
+    pub fn x_at(&self, offset: f64) -> f64 {
+        let (lo, hi) = self.x_range;
+        let t = (offset / Self::plot_width()).clamp(0.0, 1.0);
+        if self.x_log { lo * (hi / lo).powf(t) } else { lo + t * (hi - lo) }
+    }
+
+    fn y_max(&self) -> f64 {
+        let max = self.series.iter().flat_map(|s| s.marks.iter()).map(|m| m.y).fold(0.0, f64::max);
+        linear_ticks(0.0, max, 5).last().copied().filter(|&t| t >= max && t > 0.0).unwrap_or(max.max(1.0))
+    }
+
+    fn y_px(&self, y: f64, y_max: f64) -> f64 {
+        TOP + (1.0 - y / y_max) * Self::plot_height()
+    }
+
+    // readout of the marks under data x, one per series
+    pub fn readout(&self, x: f64) -> Option<String> {
+        let tips: Vec<String> = self
+            .series
+            .iter()
+            .filter_map(|s| {
+                let mark = if self.line {
+                    s.marks.iter().min_by(|a, b| (a.mid() - x).abs().total_cmp(&(b.mid() - x).abs()))
+                } else {
+                    s.marks.iter().find(|m| x >= m.x0 && x < m.x1)
+                };
+                match (mark, self.series.len()) {
+                    (Some(m), 1) => Some(m.tip.clone()),
+                    (Some(m), _) => Some(format!("{}: {}", s.name, m.tip)),
+                    (None, _) => None,
+                }
+            })
+            .collect();
+        (!tips.is_empty()).then(|| tips.join(" | "))
+    }
+
+    pub fn to_svg(&self) -> String {
+        let y_max = self.y_max();
+        let (bottom, right) = (HEIGHT - BOTTOM, WIDTH - RIGHT);
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            r##"<svg class="chart" xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11" fill="#555">"##,
+            w = WIDTH,
+            h = HEIGHT
+        );
+        let _ = write!(svg, r##"<text x="{}" y="16" font-size="13" fill="#333">{}</text>"##, LEFT, escape(&self.title));
+
+        // grid and y ticks
+        for tick in linear_ticks(0.0, y_max, 5) {
+            let y = self.y_px(tick, y_max);
+            let _ = write!(
+                svg,
+                r##"<line x1="{LEFT}" x2="{right}" y1="{y:.1}" y2="{y:.1}" stroke="#eee"/><text x="{x:.1}" y="{ty:.1}" text-anchor="end">{label}</text>"##,
+                x = LEFT - 6.0,
+                ty = y + 4.0,
+                label = tick_label(tick)
+            );
+        }
+
+        // marks
+        for (k, series) in self.series.iter().enumerate() {
+            let colour = COLOURS[k % COLOURS.len()];
+            let title = |m: &Mark| if self.series.len() > 1 { format!("{}: {}", series.name, m.tip) } else { m.tip.clone() };
+            if self.line {
+                let points: Vec<String> = series
+                    .marks
+                    .iter()
+                    .map(|m| format!("{:.1},{:.1}", self.x_px(m.mid()), self.y_px(m.y, y_max)))
+                    .collect();
+                let _ = write!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#, points.join(" "), colour);
+                for m in &series.marks {
+                    let _ = write!(
+                        svg,
+                        r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{}" fill-opacity="0"><title>{}</title></circle>"#,
+                        self.x_px(m.mid()),
+                        self.y_px(m.y, y_max),
+                        colour,
+                        escape(&title(m))
+                    );
+                }
+            } else {
+                for m in &series.marks {
+                    let (x0, x1) = (self.x_px(m.x0), self.x_px(m.x1));
+                    let y = self.y_px(m.y, y_max);
+                    let _ = write!(
+                        svg,
+                        r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}</title></rect>"#,
+                        x0 + 0.5,
+                        y,
+                        (x1 - x0 - 1.0).max(0.5),
+                        bottom - y,
+                        colour,
+                        escape(&title(m))
+                    );
+                }
+            }
+        }
+
+        // reference lines, labelled along the top of the plot
+        for (k, (x, label)) in self.markers.iter().enumerate() {
+            let px = self.x_px(*x);
+            let dash = if k % 2 == 0 { "4 3" } else { "none" };
+            let _ = write!(
+                svg,
+                r##"<line x1="{px:.1}" x2="{px:.1}" y1="{TOP}" y2="{bottom}" stroke="#333" stroke-dasharray="{dash}"><title>{label}</title></line><text x="{tx:.1}" y="{ty:.1}" fill="#333">{label}</text>"##,
+                tx = px + 3.0,
+                ty = TOP + 10.0 + 12.0 * k as f64,
+                label = escape(label)
+            );
+        }
+
+        // legend in the top right corner of the plot
+        if self.series.len() > 1 {
+            for (k, series) in self.series.iter().enumerate() {
+                let (x, y) = (right - 150.0, TOP + 4.0 + 14.0 * k as f64);
+                let name: String = series.name.chars().take(24).collect();
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{x}" y="{y}" width="10" height="10" fill="{}"/><text x="{tx}" y="{ty}">{}</text>"#,
+                    COLOURS[k % COLOURS.len()],
+                    escape(&name),
+                    tx = x + 14.0,
+                    ty = y + 9.0
+                );
+            }
+        }
+
+        // axes and x ticks
+        let _ = write!(
+            svg,
+            r##"<line x1="{LEFT}" x2="{right}" y1="{bottom}" y2="{bottom}" stroke="#999"/><line x1="{LEFT}" x2="{LEFT}" y1="{TOP}" y2="{bottom}" stroke="#999"/>"##
+        );
+        let (lo, hi) = self.x_range;
+        let ticks = if self.x_log { log_ticks(lo, hi) } else { linear_ticks(lo, hi, 6) };
+        for tick in ticks.into_iter().filter(|&t| t >= lo && t <= hi) {
+            let x = self.x_px(tick);
+            let _ = write!(
+                svg,
+                r##"<line x1="{x:.1}" x2="{x:.1}" y1="{bottom}" y2="{tb}" stroke="#999"/><text x="{x:.1}" y="{ty}" text-anchor="middle">{label}</text>"##,
+                tb = bottom + 4.0,
+                ty = bottom + 16.0,
+                label = tick_label(tick)
+            );
+        }
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+            LEFT + Self::plot_width() / 2.0,
+            HEIGHT - 6.0,
+            escape(&self.x_label)
+        );
+        let _ = write!(
+            svg,
+            r#"<text transform="translate(14 {:.1}) rotate(-90)" text-anchor="middle">{}</text>"#,
+            TOP + Self::plot_height() / 2.0,
+            escape(&self.y_label)
+        );
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+// a file's data to plot; the name labels it in the legend of overlays
+pub struct Input<'a, T> {
+    pub name: &'a str,
+    pub data: &'a [T],
+}
+
+// share of a total in percent, for normalised overlays
+fn percent(value: f64, total: f64) -> f64 {
+    if total > 0.0 { value / total * 100.0 } else { 0.0 }
+}
+
+// length histograms on the table's bins, or on bins of the same kind within a zoomed range
+pub fn length_chart(files: &[Input<i64>], length_bins: &LengthBins, plot_type: &str, zoom: Option<(f64, f64)>, normalise: bool) -> Chart {
+    let zoomed;
+    let (range, length_bins) = match zoom {
+        Some((lo, hi)) => {
+            let (lo, hi) = (lo.max(0.0) as usize, hi.max(lo + 1.0) as usize);
+            let count = length_bins.len();
+            zoomed = if length_bins.log { LengthBins::log(lo, hi, count) } else { LengthBins::auto(lo, hi, count) };
+            (Some(lo..=hi), &zoomed)
+        }
+        None => (None, length_bins),
+    };
+    let by_bases = plot_type == "bases";
+
+    let series = files
+        .iter()
+        .map(|f| {
+            let lengths: Vec<i64> = match &range {
+                Some(range) => f.data.iter().copied().filter(|&l| range.contains(&(l as usize))).collect(),
+                None => f.data.to_vec(),
+            };
+            let (reads, bases) = length_bins.count(&lengths);
+            let total_reads = reads.iter().sum::<u64>() as f64;
+            let total_bases = bases.iter().sum::<usize>() as f64;
+            let marks = (0..length_bins.len())
+                .map(|i| {
+                    let y = match (by_bases, normalise) {
+                        (true, true) => percent(bases[i] as f64, total_bases),
+                        (true, false) => bases[i] as f64,
+                        (false, true) => percent(reads[i] as f64, total_reads),
+                        (false, false) => reads[i] as f64,
+                    };
+                    Mark {
+                        x0: length_bins.start(i) as f64,
+                        x1: length_bins.end(i) as f64,
+                        y,
+                        tip: format!(
+                            "{} bp: {} reads ({:.1}%), {} bases ({:.1}%)",
+                            length_bins.label(i),
+                            reads[i].human_count_bare(),
+                            percent(reads[i] as f64, total_reads),
+                            bases[i].human_count_bare(),
+                            percent(bases[i] as f64, total_bases)
+                        ),
+                    }
+                })
+                .collect();
+            Series { name: f.name.to_string(), marks }
+        })
+        .collect();
+    let unit = if by_bases { "Bases" } else { "Reads" };
+    Chart {
+        title: format!("{} length histogram", unit),
+        x_label: "Read length (bp)".to_string(),
+        y_label: if normalise { format!("% of {}", unit.to_lowercase()) } else { unit.to_string() },
+        x_log: length_bins.log,
+        x_range: (length_bins.start(0) as f64, length_bins.end(length_bins.len() - 1) as f64),
+        line: files.len() > 1,
+        series,
+        markers: Vec::new(),
+    }
+}
+
+// bases in reads at least x long, over the length range of the files or the zoomed range
+pub fn yield_chart(files: &[Input<i64>], log: bool, zoom: Option<(f64, f64)>, normalise: bool) -> Chart {
+    let min = files.iter().flat_map(|f| f.data.iter()).copied().min().unwrap_or(0) as f64;
+    let max = files.iter().flat_map(|f| f.data.iter()).copied().max().unwrap_or(1) as f64;
+    let (lo, hi) = zoom.unwrap_or((min, max.max(min + 1.0)));
+    let lo = if log { lo.max(1.0) } else { lo };
+
+    let series = files
+        .iter()
+        .map(|f| {
+            let mut lengths: Vec<i64> = f.data.to_vec();
+            lengths.sort_unstable();
+            let mut above: Vec<u64> = vec![0; lengths.len() + 1]; // bases in lengths[i..]
+            for i in (0..lengths.len()).rev() {
+                above[i] = above[i + 1] + lengths[i] as u64;
+            }
+            let total = above[0] as f64;
+            let marks = (0..=YIELD_POINTS)
+                .map(|i| {
+                    let t = i as f64 / YIELD_POINTS as f64;
+                    let x = if log { lo * (hi / lo).powf(t) } else { lo + t * (hi - lo) };
+                    let bases = above[lengths.partition_point(|&l| (l as f64) < x)];
+                    Mark {
+                        x0: x,
+                        x1: x,
+                        y: if normalise { percent(bases as f64, total) } else { bases as f64 },
+                        tip: format!(
+                            "reads ≥ {} bp: {} bases ({:.1}%)",
+                            (x.round() as u64).human_count_bare(),
+                            bases.human_count_bare(),
+                            percent(bases as f64, total)
+                        ),
+                    }
+                })
+                .collect();
+            Series { name: f.name.to_string(), marks }
+        })
+        .collect();
+    Chart {
+        title: "Base yield over length".to_string(),
+        x_label: "Minimum read length (bp)".to_string(),
+        y_label: if normalise { "% of bases" } else { "Bases" }.to_string(),
+        x_log: log,
+        x_range: (lo, hi),
+        line: true,
+        series,
+        markers: Vec::new(),
+    }
+}
+
+// per-read mean quality, one bar per Q value
+pub fn quality_chart(files: &[Input<u8>], normalise: bool) -> Chart {
+    let max_q = files.iter().flat_map(|f| f.data.iter()).copied().max().unwrap_or(0) as usize;
+    let series = files
+        .iter()
+        .map(|f| {
+            let mut counts = vec![0u64; max_q + 1];
+            for &q in f.data {
+                counts[q as usize] += 1;
+            }
+            let total = f.data.len() as f64;
+            let marks = counts
+                .iter()
+                .enumerate()
+                .map(|(q, &n)| Mark {
+                    x0: q as f64,
+                    x1: q as f64 + 1.0,
+                    y: if normalise { percent(n as f64, total) } else { n as f64 },
+                    tip: format!("Q{}: {} reads ({:.1}%)", q, n.human_count_bare(), percent(n as f64, total)),
+                })
+                .collect();
+            Series { name: f.name.to_string(), marks }
+        })
+        .collect();
+    // mean and median of a single file, overlays would be too busy
+    let markers = match files {
+        [f] if !f.data.is_empty() => {
+            let mean = f.data.iter().map(|&q| q as f64).sum::<f64>() / f.data.len() as f64;
+            let median = crate::modules::median(&mut f.data.to_vec()) as f64; // as in the table
+            // bars span q..q + 1, so the lines sit at the middle of their bar
+            vec![(mean + 0.5, format!("mean Q{:.1}", mean)), (median + 0.5, format!("median Q{}", median))]
+        }
+        _ => Vec::new(),
+    };
+    Chart {
+        title: "Read quality histogram".to_string(),
+        x_label: "Mean read Qscore".to_string(),
+        y_label: if normalise { "% of reads" } else { "Reads" }.to_string(),
+        x_log: false,
+        x_range: (0.0, (max_q + 1) as f64),
+        line: files.len() > 1,
+        series,
+        markers,
+    }
+}
+
+// mean base quality along the reads; positions are 1-based and binned as in Positions
+pub fn position_quality_chart(name: &str, positions: &Positions) -> Chart {
+    let marks = (0..positions.bins.len())
+        .filter_map(|bin| {
+            let q = positions.mean_quality(bin)?;
+            Some(Mark {
+                x0: (Positions::start(bin) + 1) as f64,
+                x1: Positions::end(bin) as f64,
+                y: q,
+                tip: format!("position {}: mean Q{:.1}", Positions::label(bin), q),
+            })
+        })
+        .collect();
+    position_chart("Quality along the reads", "Mean base Qscore", vec![Series { name: name.to_string(), marks }])
+}
+
+// share of each base along the reads, one line per base
+pub fn composition_chart(positions: &Positions) -> Chart {
+    let series = BASES
+        .iter()
+        .enumerate()
+        .map(|(base, name)| Series {
+            name: name.to_string(),
+            marks: (0..positions.bins.len())
+                .map(|bin| {
+                    let share = positions.percent(bin, base);
+                    Mark {
+                        x0: (Positions::start(bin) + 1) as f64,
+                        x1: Positions::end(bin) as f64,
+                        y: share,
+                        tip: format!("position {}: {:.1}%", Positions::label(bin), share),
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    position_chart("Base composition along the reads", "% of bases", series)
+}
+
+// long reads get a log axis, like their length bins
+fn position_chart(title: &str, y_label: &str, series: Vec<Series>) -> Chart {
+    let end = series.iter().flat_map(|s| s.marks.iter()).map(|m| m.x1).fold(1.0, f64::max);
+    Chart {
+        title: title.to_string(),
+        x_label: "Position in read (bp)".to_string(),
+        y_label: y_label.to_string(),
+        x_log: end > 1000.0,
+        x_range: (1.0, end.max(2.0)),
+        line: true,
+        series,
+        markers: Vec::new(),
+    }
+}
+
+// log length x Q heatmap; cell shade follows the log of the read count
+pub fn density_svg(density: &Density) -> String {
+    let (bottom, right) = (HEIGHT - BOTTOM, WIDTH - RIGHT);
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<svg class="chart" xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="11" fill="#555">"##,
+        w = WIDTH,
+        h = HEIGHT
+    );
+    let _ = write!(svg, r##"<text x="{}" y="16" font-size="13" fill="#333">Read length vs mean quality</text>"##, LEFT);
+    let Some((first, last, top)) = density.extent() else {
+        svg.push_str("</svg>");
+        return svg;
+    };
+    let (x_lo, x_hi) = (Density::length_start(first), Density::length_start(last + 1));
+    let q_hi = (top + 1) as f64;
+    let x_px = |x: f64| LEFT + (x.ln() - x_lo.ln()) / (x_hi.ln() - x_lo.ln()) * Chart::plot_width();
+    let y_px = |q: f64| bottom - q / q_hi * Chart::plot_height();
+    let log_max = (density.max() as f64 + 1.0).ln();
+
+    for q in 0..=top {
+        for bin in first..=last {
+            let n = density.get(bin, q);
+            if n == 0 {
+                continue;
+            }
+            let (x0, x1) = (Density::length_start(bin), Density::length_start(bin + 1));
+            let shade = 0.15 + 0.85 * (n as f64 + 1.0).ln() / log_max;
+            let _ = write!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" fill-opacity="{:.2}"><title>{}-{} bp, Q{}: {} reads</title></rect>"#,
+                x_px(x0),
+                y_px(q as f64 + 1.0),
+                x_px(x1) - x_px(x0),
+                y_px(q as f64) - y_px(q as f64 + 1.0),
+                COLOURS[0],
+                shade,
+                (x0.round() as u64).human_count_bare(),
+                (x1.round() as u64).human_count_bare(),
+                q,
+                n.human_count_bare()
+            );
+        }
+    }
+
+    // axes and ticks
+    let _ = write!(
+        svg,
+        r##"<line x1="{LEFT}" x2="{right}" y1="{bottom}" y2="{bottom}" stroke="#999"/><line x1="{LEFT}" x2="{LEFT}" y1="{TOP}" y2="{bottom}" stroke="#999"/>"##
+    );
+    for tick in log_ticks(x_lo, x_hi).into_iter().filter(|&t| t >= x_lo && t <= x_hi) {
+        let x = x_px(tick);
+        let _ = write!(
+            svg,
+            r##"<line x1="{x:.1}" x2="{x:.1}" y1="{bottom}" y2="{tb}" stroke="#999"/><text x="{x:.1}" y="{ty}" text-anchor="middle">{label}</text>"##,
+            tb = bottom + 4.0,
+            ty = bottom + 16.0,
+            label = tick_label(tick)
+        );
+    }
+    for tick in linear_ticks(0.0, q_hi, 5).into_iter().filter(|&t| t <= q_hi) {
+        let y = y_px(tick);
+        let _ = write!(
+            svg,
+            r##"<line x1="{tl}" x2="{LEFT}" y1="{y:.1}" y2="{y:.1}" stroke="#999"/><text x="{x:.1}" y="{ty:.1}" text-anchor="end">{label}</text>"##,
+            tl = LEFT - 4.0,
+            x = LEFT - 6.0,
+            ty = y + 4.0,
+            label = tick_label(tick)
+        );
+    }
+    let _ = write!(
+        svg,
+        r#"<text x="{:.1}" y="{}" text-anchor="middle">Read length (bp)</text><text transform="translate(14 {:.1}) rotate(-90)" text-anchor="middle">Mean read Qscore</text>"#,
+        LEFT + Chart::plot_width() / 2.0,
+        HEIGHT - 6.0,
+        TOP + Chart::plot_height() / 2.0
+    );
+    svg.push_str("</svg>");
+    svg
+}
+
+// round steps of 1, 2 or 5 x 10^k, about n of them
+fn linear_ticks(lo: f64, hi: f64, n: usize) -> Vec<f64> {
+    let span = hi - lo;
+    if span <= 0.0 || !span.is_finite() {
+        return vec![lo];
+    }
+    let raw = span / n as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].iter().map(|s| s * magnitude).find(|&s| s >= raw).unwrap_or(10.0 * magnitude);
+    let first = (lo / step).ceil() * step;
+    let mut ticks = Vec::new();
+    let mut tick = first;
+    while tick <= hi + step * 0.5 {
+        ticks.push(tick);
+        tick += step;
+    }
+    ticks
+}
+
+// powers of ten, with 2 and 5 multiples when the range is narrow
+fn log_ticks(lo: f64, hi: f64) -> Vec<f64> {
+    let lo = lo.max(1.0);
+    let (first, last) = (lo.log10().floor() as i32, hi.log10().ceil() as i32);
+    let multiples: &[f64] = if last - first <= 2 { &[1.0, 2.0, 5.0] } else { &[1.0] };
+    (first..=last)
+        .flat_map(|e| multiples.iter().map(move |m| m * 10f64.powi(e)))
+        .collect()
+}
+
+fn tick_label(value: f64) -> String {
+    if value >= 1000.0 {
+        (value.round() as u64).human_count_bare().to_string()
+    } else if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,121 @@
+// Command line subcommands of the desktop build; without one the app window opens
+
+use std::fs::File;
+use std::io::Read;
+
+use flate2::read::MultiGzDecoder;
+
+use crate::{groups, metrics, sketch, sources, stats};
+
+const USAGE: &str = "usage:
+  fasterx                                   open the app
+  fasterx stats FILE...                     read statistics and yield matrix, per file and pooled
+  fasterx sketch [-o OUT.json] FILE...      sketch genomes or reads, one sketch per file";
+
+// Some(exit code) if the arguments name a subcommand
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.get(1)?;
+    let rest = &args[2..];
+    let result = match command.as_str() {
+        "stats" => print_stats(rest),
+        "sketch" => sketch_files(rest),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => return None,
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("fasterx {}: {}\n\n{}", command, e, USAGE);
+            Some(1)
+        }
+    }
+}
+
+fn open(path: &str) -> Result<Box<dyn Read + Send>, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+    if path.ends_with(".gz") {
+        Ok(Box::new(MultiGzDecoder::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+// summary line and yield matrix of every file, then of all files pooled
+fn print_stats(args: &[String]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err("no input files".to_string());
+    }
+    let mut files = Vec::new();
+    for input in args {
+        let mut file_stats = stats::FileStats::new(input);
+        for rec in sources::records(open(input)?, sources::is_fasta(input)) {
+            file_stats.add(&rec);
+        }
+        files.push(file_stats.finish());
+    }
+
+    println!("file\treads\tbases\tN50\tGC%\tQ20%\tQ30%\tmedian_Q");
+    for f in &files {
+        let values: Vec<String> = ["reads", "bases", "nx", "gc", "q20", "q30", "m_qscore"]
+            .iter()
+            .map(|key| metrics::column(f, key).export())
+            .collect();
+        println!("{}\t{}", f.name, values.join("\t"));
+    }
+    for f in &files {
+        println!("\n# {}: bases with length >= L and mean Q >= Q\n{}", f.name, f.yields.to_text());
+    }
+    if files.len() > 1 {
+        let pooled = groups::pool_files("all", "all", &files.iter().collect::<Vec<_>>());
+        println!("# all {} files: bases with length >= L and mean Q >= Q\n{}", files.len(), pooled.yields.to_text());
+    }
+    Ok(())
+}
+
+// writes the sketches of all inputs to one file, a folder of these is a sketch database
+fn sketch_files(args: &[String]) -> Result<(), String> {
+    let mut output = "sketches.json".to_string();
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = args.next().ok_or("-o needs a file name")?.clone(),
+            _ => inputs.push(arg.clone()),
+        }
+    }
+    if inputs.is_empty() {
+        return Err("no input files".to_string());
+    }
+
+    let mut sketches = Vec::new();
+    for input in inputs {
+        let mut named = sketch::NamedSketch {
+            name: sources::file_stem(&input),
+            reads: 0,
+            saved: true,
+            sketch: sketch::Sketch::default(),
+        };
+        // genomes keep every k-mer, reads drop those seen once
+        let fasta = sources::is_fasta(&input);
+        let mut reads = sketch::ReadSketch::default();
+        for rec in sources::records(open(&input)?, fasta) {
+            if fasta {
+                named.sketch.add(rec.seq());
+            } else {
+                reads.add(rec.seq());
+            }
+            named.reads += 1;
+        }
+        if !fasta {
+            named.sketch = reads.sketch;
+        }
+        eprintln!("{}: {} sequences", named.name, named.reads);
+        sketches.push(named);
+    }
+    std::fs::write(&output, sketch::to_json(&sketches)).map_err(|e| format!("{}: {}", output, e))?;
+    eprintln!("wrote {} sketches to {}", sketches.len(), output);
+    Ok(())
+}
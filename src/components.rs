@@ -1,4 +1,7 @@
 use dioxus::prelude::*;
+use indicatif::HumanCount;
+
+use crate::charts;
 
 pub fn app_title(filescount: Signal<usize>) -> Element {
     rsx! {
@@ -20,4 +23,996 @@ pub fn app_title(filescount: Signal<usize>) -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
+// Index analysis for Illumina files carrying index sequences in the headers
+pub fn index_panel(files: Signal<Vec<crate::UploadedFile>>, mut samplesheet: Signal<String>) -> Element {
+    let samples = crate::indexes::parse_samplesheet(&samplesheet.read());
+
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Index analysis" }
+            p {
+                "Paste a sample sheet ([Data] section or name,i7,i5 lines) to match barcodes allowing 1 mismatch per index. "
+                "{samples.len()} samples loaded."
+            }
+            textarea {
+                class: "samplesheet-input",
+                rows: "5",
+                placeholder: "Sample_ID,index,index2",
+                value: "{samplesheet}",
+                oninput: move |ev| samplesheet.set(ev.value()),
+            }
+            for f in files.read().iter().filter(|f| !f.index_counts.is_empty()) {
+                {index_table(f, &samples)}
+            }
+        }
+    }
+}
+
+fn index_table(f: &crate::UploadedFile, samples: &[crate::indexes::SampleIndex]) -> Element {
+    let indexed: u64 = f.index_counts.values().sum();
+    let assigned: u64 = f.index_counts
+        .iter()
+        .filter(|(bc, _)| crate::indexes::assign(bc, samples).is_some())
+        .map(|(_, &n)| n)
+        .sum();
+    let assigned_percent = assigned as f64 / indexed as f64 * 100.0;
+    let top = crate::indexes::top_barcodes(&f.index_counts, 20);
+
+    rsx! {
+        h5 { "{f.basename}" }
+        p {
+            "{HumanCount(indexed)} indexed reads, {HumanCount(f.index_counts.len() as u64)} distinct barcodes"
+            if !samples.is_empty() {
+                ", {assigned_percent:.2}% match the sample sheet"
+            }
+        }
+        table {
+            class: "index-table",
+            thead {
+                tr {
+                    th { class: "histogram-header", "Barcode" }
+                    th { "Reads" }
+                    th { "%" }
+                    th { class: "histogram-header", "Sample" }
+                    th { class: "histogram-header", "Hint" }
+                }
+            }
+            tbody {
+                for (barcode, count) in top {
+                    tr {
+                        td { class: "filename-cell", "{barcode}" }
+                        td { "{HumanCount(count)}" }
+                        td { {format!("{:.2}", count as f64 / indexed as f64 * 100.0)} }
+                        match crate::indexes::assign(barcode, samples) {
+                            Some(s) => rsx! {
+                                td { class: "filename-cell", "{s.name}" }
+                                td {}
+                            },
+                            None => rsx! {
+                                td { class: "filename-cell", "—" }
+                                td { class: "filename-cell", {crate::indexes::hint(barcode, samples)} }
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// files found in a selected folder, filtered with include/exclude globs before loading
+pub fn folder_preview(
+    files: Vec<String>,
+    mut include: Signal<String>,
+    mut exclude: Signal<String>,
+    mut on_load: impl FnMut(Vec<String>) + 'static,
+    on_cancel: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let selected: Vec<String> = files
+        .iter()
+        .filter(|f| crate::sources::filter_name(f, &include.read(), &exclude.read()))
+        .cloned()
+        .collect();
+    let shown = selected.len().min(100);
+    let count = selected.len();
+
+    rsx! {
+        div {
+            class: "panel folder-preview",
+            h4 { "Add folder" }
+            p { "{count} of {files.len()} sequence files selected" }
+            div {
+                class: "controls-row",
+                input {
+                    r#type: "text",
+                    class: "usercontrols text-input",
+                    placeholder: "include, e.g. *.fastq.gz",
+                    value: "{include}",
+                    oninput: move |ev| include.set(ev.value()),
+                }
+                input {
+                    r#type: "text",
+                    class: "usercontrols text-input",
+                    placeholder: "exclude, e.g. *unclassified*",
+                    value: "{exclude}",
+                    oninput: move |ev| exclude.set(ev.value()),
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: count == 0,
+                    onclick: move |_| on_load(selected.clone()),
+                    "Load {count} files"
+                }
+                button {
+                    class: "usercontrols usercontrols-cancel",
+                    onclick: on_cancel,
+                    "Cancel"
+                }
+            }
+            ul {
+                class: "file-list",
+                for f in files.iter().filter(|f| crate::sources::filter_name(f, &include.read(), &exclude.read())).take(shown) {
+                    li { "{f}" }
+                }
+                if count > shown {
+                    li { "... and {count - shown} more" }
+                }
+            }
+        }
+    }
+}
+
+// numeric input bound to one field of a settings signal
+fn number_input<T: Clone + 'static>(
+    label: &str,
+    value: String,
+    mut settings: Signal<T>,
+    update: fn(&mut T, f64),
+) -> Element {
+    rsx! {
+        label {
+            class: "field-label",
+            "{label}"
+            input {
+                r#type: "number",
+                class: "usercontrols text-input",
+                min: "0",
+                value: "{value}",
+                oninput: move |ev| {
+                    if let Ok(v) = ev.value().parse::<f64>() {
+                        if v >= 0.0 {
+                            update(&mut settings.write(), v);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// settings for writing length/quality filtered reads
+pub fn filter_panel(
+    filter: Signal<crate::filter::ReadFilter>,
+    status: Signal<String>,
+    on_run: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let f = filter();
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Filter reads" }
+            p { "Reads are trimmed first, then filtered. Each file is written as a new gzipped fastq file and added to the table." }
+            div {
+                class: "controls-row",
+                {number_input("Min length", f.min_length.to_string(), filter, |f, v| f.min_length = v as usize)}
+                {number_input("Max length (0 = none)", f.max_length.to_string(), filter, |f, v| f.max_length = v as usize)}
+                {number_input("Min mean Q", f.min_qscore.to_string(), filter, |f, v| f.min_qscore = v.min(93.0) as u8)}
+                {number_input("Max N%", f.max_n_percent.to_string(), filter, |f, v| f.max_n_percent = v)}
+                {number_input("Trim head", f.trim_head.to_string(), filter, |f, v| f.trim_head = v as usize)}
+                {number_input("Trim tail", f.trim_tail.to_string(), filter, |f, v| f.trim_tail = v as usize)}
+                button {
+                    class: "usercontrols",
+                    onclick: on_run,
+                    "Write filtered reads"
+                }
+            }
+            if !status().is_empty() {
+                p { "{status}" }
+            }
+        }
+    }
+}
+
+// settings for writing a random or best-reads subsample of each file
+pub fn subsample_panel(
+    mut settings: Signal<crate::subsample::Subsample>,
+    status: Signal<String>,
+    on_run: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let s = settings();
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Subsample" }
+            p { "Each file is downsampled and written as a new gzipped fastq file. The same seed gives the same reads." }
+            div {
+                class: "controls-row",
+                select {
+                    class: "usercontrols",
+                    oninput: move |ev| settings.write().target = ev.value(),
+                    option { value: "reads", selected: s.target == "reads", "Number of reads" }
+                    option { value: "bases", selected: s.target == "bases", "Number of bases" }
+                    option { value: "coverage", selected: s.target == "coverage", "Coverage (x)" }
+                }
+                {number_input("Amount", s.amount.to_string(), settings, |s, v| s.amount = v)}
+                if s.target == "coverage" {
+                    {number_input("Genome size (bp)", s.genome_size.to_string(), settings, |s, v| s.genome_size = v as u64)}
+                }
+                select {
+                    class: "usercontrols",
+                    oninput: move |ev| settings.write().prefer = ev.value(),
+                    option { value: "random", selected: s.prefer == "random", "Random reads" }
+                    option { value: "longest", selected: s.prefer == "longest", "Longest reads" }
+                    option { value: "quality", selected: s.prefer == "quality", "Highest quality reads" }
+                }
+                if s.prefer == "random" {
+                    {number_input("Seed", s.seed.to_string(), settings, |s, v| s.seed = v as u64)}
+                }
+                button {
+                    class: "usercontrols",
+                    onclick: on_run,
+                    "Write subsample"
+                }
+            }
+            if !status().is_empty() {
+                p { "{status}" }
+            }
+        }
+    }
+}
+
+// paged view of the reads of one file
+#[allow(clippy::too_many_arguments)]
+pub fn read_browser(
+    files: Vec<String>,
+    mut selected: Signal<String>,
+    index: Signal<Option<std::sync::Arc<crate::reads::ReadIndex>>>,
+    records: Vec<(usize, bio::io::fastq::Record)>, // the current page
+    loading: bool,
+    mut page: Signal<usize>,
+    mut limits: Signal<(u32, u8)>, // min length, min mean Q
+    on_open: impl FnMut(String) + Clone + 'static,
+) -> Element {
+    use crate::reads::PAGE_SIZE;
+    let guard = index.read();
+    let matching = guard.as_ref().map(|idx| idx.matching(limits().0, limits().1)).unwrap_or_default();
+    let pages = matching.len().div_ceil(PAGE_SIZE).max(1);
+    let current = page().min(pages - 1);
+    let (min_length, min_q) = limits();
+
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Read browser" }
+            div {
+                class: "controls-row",
+                select {
+                    class: "usercontrols",
+                    oninput: {
+                        let mut on_open = on_open.clone();
+                        move |ev: FormEvent| {
+                            selected.set(ev.value());
+                            page.set(0);
+                            on_open(ev.value());
+                        }
+                    },
+                    option { value: "", disabled: true, selected: selected().is_empty(), "Choose a file" }
+                    for f in files.iter() {
+                        option { value: "{f}", selected: *f == selected(), "{f}" }
+                    }
+                }
+                label {
+                    class: "field-label",
+                    "Min length"
+                    input {
+                        r#type: "number",
+                        class: "usercontrols text-input",
+                        min: "0",
+                        value: "{min_length}",
+                        oninput: move |ev| {
+                            if let Ok(v) = ev.value().parse::<u32>() {
+                                limits.set((v, limits().1));
+                                page.set(0);
+                            }
+                        }
+                    }
+                }
+                label {
+                    class: "field-label",
+                    "Min mean Q"
+                    input {
+                        r#type: "number",
+                        class: "usercontrols text-input",
+                        min: "0",
+                        value: "{min_q}",
+                        oninput: move |ev| {
+                            if let Ok(v) = ev.value().parse::<u8>() {
+                                limits.set((limits().0, v));
+                                page.set(0);
+                            }
+                        }
+                    }
+                }
+                label {
+                    class: "field-label",
+                    "Go to read"
+                    input {
+                        r#type: "number",
+                        class: "usercontrols text-input",
+                        min: "1",
+                        onchange: {
+                            let matching = matching.clone();
+                            move |ev: FormEvent| {
+                                if let Ok(n) = ev.value().parse::<usize>() {
+                                    // page holding record n, or the first match after it
+                                    let pos = matching.partition_point(|&i| i + 1 < n);
+                                    page.set(pos / PAGE_SIZE);
+                                }
+                            }
+                        }
+                    }
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: current == 0,
+                    onclick: move |_| page.set(current.saturating_sub(1)),
+                    "◀"
+                }
+                span { "Page {current + 1} of {pages}" }
+                button {
+                    class: "usercontrols",
+                    disabled: current + 1 >= pages,
+                    onclick: move |_| page.set(current + 1),
+                    "▶"
+                }
+            }
+            if loading {
+                p { "Indexing reads..." }
+            } else if let Some(idx) = guard.as_ref() {
+                p { "{HumanCount(matching.len() as u64)} of {HumanCount(idx.len() as u64)} reads match" }
+                div {
+                    class: "read-list",
+                    for (i, rec) in records {
+                        div {
+                            class: "read-record",
+                            div {
+                                class: "read-header",
+                                span { class: "read-number", "#{i + 1}" }
+                                span { "{rec.id()}" }
+                                span { class: "read-desc", {rec.desc().unwrap_or_default().to_string()} }
+                                span { "{rec.seq().len()} bp, mean Q {crate::modules::qscore_mean(rec.qual())}" }
+                            }
+                            div {
+                                class: "read-seq",
+                                dangerous_inner_html: crate::reads::quality_coloured(rec.seq(), rec.qual(), 2000)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// motif / primer counts per file
+pub fn search_panel(
+    mut motifs: Signal<String>,
+    mut mismatches: Signal<usize>,
+    results: Signal<Vec<crate::search::MotifResult>>,
+    on_run: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let parsed = crate::search::parse_motifs(&motifs.read());
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Motif search" }
+            p {
+                "One sequence per line (IUPAC codes allowed, optionally name=SEQUENCE). Reads are searched on both strands. "
+                "{parsed.len()} motifs."
+            }
+            textarea {
+                class: "samplesheet-input",
+                rows: "3",
+                placeholder: "ONT_adapter=AATGTACTTCGTTCAGTTACGTATTGCT",
+                value: "{motifs}",
+                oninput: move |ev| motifs.set(ev.value()),
+            }
+            div {
+                class: "controls-row",
+                label {
+                    class: "field-label",
+                    "Mismatches"
+                    input {
+                        r#type: "number",
+                        class: "usercontrols text-input",
+                        min: "0",
+                        max: "5",
+                        value: "{mismatches}",
+                        oninput: move |ev| {
+                            if let Ok(v) = ev.value().parse::<usize>() {
+                                mismatches.set(v);
+                            }
+                        }
+                    }
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: parsed.is_empty(),
+                    onclick: on_run,
+                    "Search"
+                }
+            }
+            if !results.read().is_empty() {
+                table {
+                    class: "index-table",
+                    thead {
+                        tr {
+                            th { class: "histogram-header", "File" }
+                            th { class: "histogram-header", "Motif" }
+                            th { "Reads" }
+                            th { "%" }
+                            th { "Forward" }
+                            th { "Rev. comp." }
+                            th { class: "histogram-header", "Match position in read" }
+                        }
+                    }
+                    tbody {
+                        for r in results.read().iter() {
+                            tr {
+                                td { class: "filename-cell", "{r.file}" }
+                                td { class: "filename-cell", "{r.motif}" }
+                                td { "{HumanCount(r.hits)}" }
+                                td { {format!("{:.2}", r.percent())} }
+                                td { "{HumanCount(r.fwd)}" }
+                                td { "{HumanCount(r.rc)}" }
+                                td {
+                                    class: "histogram-cell",
+                                    dangerous_inner_html: r.position_histogram()
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn screen_panel(
+    mut references: Signal<crate::screen::ReferenceIndex>,
+    on_add: impl FnMut(FormEvent) + 'static,
+    on_run: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Contamination screen" }
+            p {
+                "Load reference FASTA files (e.g. phiX, lambda, E. coli, human mitochondrion), one reference per file. "
+                "A seeded random sample of up to {HumanCount(crate::screen::SCREEN_SAMPLE as u64)} reads, spread over each file, is matched "
+                "by shared minimizers and the percentage of sampled reads hitting each reference is added to the table."
+            }
+            div {
+                class: "controls-row",
+                label {
+                    r#for: "referencereader",
+                    class: "usercontrols",
+                    "Add references"
+                }
+                input {
+                    id: "referencereader",
+                    class: "file-input-hidden",
+                    r#type: "file",
+                    accept: ".fasta,.fa,.fna,.fas,.gz",
+                    multiple: true,
+                    onchange: on_add
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: references.read().is_empty(),
+                    onclick: move |_| references.set(Default::default()),
+                    "Clear references"
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: references.read().is_empty(),
+                    onclick: on_run,
+                    "Screen files"
+                }
+            }
+            if !references.read().is_empty() {
+                p { "References: {references.read().names.join(\", \")}" }
+            }
+        }
+    }
+}
+
+// pairwise Jaccard similarity of the MinHash sketches, rows in cluster order
+pub fn similarity_panel(
+    similarity: Memo<(Vec<crate::sketch::NamedSketch>, Vec<Vec<f64>>)>,
+    threshold: Signal<f64>,
+    mut saved: Signal<Vec<crate::sketch::NamedSketch>>,
+    status: Signal<String>,
+    on_sketch: impl FnMut(MouseEvent) + 'static,
+    on_import: impl FnMut(FormEvent) + 'static,
+    on_export: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let (sketches, matrix) = &*similarity.read();
+    let groups = crate::sketch::similar_groups(matrix, threshold());
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Sample similarity" }
+            p {
+                "Jaccard similarity of {crate::sketch::K}-mer MinHash sketches ({crate::sketch::SIZE} hashes per file); "
+                "k-mers seen only once, mostly sequencing errors, are left out. "
+                "Swapped or duplicated samples show up as unexpected pairs of highly similar files. "
+                "Sketches can be exported and compared against those of previous sessions."
+            }
+            div {
+                class: "controls-row",
+                {number_input("Similar at ≥", format!("{}", threshold()), threshold, |t, v| *t = v.min(1.0))}
+                button {
+                    class: "usercontrols",
+                    onclick: on_sketch,
+                    "Sketch files"
+                }
+                button {
+                    class: "usercontrols",
+                    onclick: on_export,
+                    "Export sketches"
+                }
+                label {
+                    r#for: "sketchreader",
+                    class: "usercontrols",
+                    "Import sketches"
+                }
+                input {
+                    id: "sketchreader",
+                    class: "file-input-hidden",
+                    r#type: "file",
+                    accept: ".json",
+                    multiple: true,
+                    onchange: on_import
+                }
+                if !saved.read().is_empty() {
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| saved.set(Vec::new()),
+                        "Clear imported"
+                    }
+                }
+            }
+            if !status.read().is_empty() {
+                p { "{status}" }
+            }
+            if groups.is_empty() {
+                p { "No files with similarity ≥ {threshold()}." }
+            }
+            for group in groups.iter() {
+                p {
+                    "Similar: "
+                    {group.iter().map(|&i| sketches[i].name.clone()).collect::<Vec<_>>().join(", ")}
+                }
+            }
+            table {
+                class: "index-table similarity-table",
+                thead {
+                    tr {
+                        th { class: "histogram-header", "File" }
+                        for i in 0..sketches.len() {
+                            th { class: "similarity-index", "{i + 1}" }
+                        }
+                    }
+                }
+                tbody {
+                    for (i, (s, row)) in sketches.iter().zip(matrix.iter()).enumerate() {
+                        tr {
+                            td {
+                                class: if s.saved { "filename-cell saved-sketch" } else { "filename-cell" },
+                                title: "{HumanCount(s.reads)} reads",
+                                "{i + 1}. {s.name}"
+                                if s.saved { " (imported)" }
+                            }
+                            for &j in row.iter() {
+                                td {
+                                    class: "similarity-cell",
+                                    style: "background-color: rgba(46, 134, 193, {j:.2})",
+                                    "{j:.2}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// top matches of every file in a reference sketch database
+pub fn species_panel(
+    mut db: Signal<crate::sketch::SketchDb>,
+    results: Signal<Vec<(String, Vec<crate::sketch::Match>)>>,
+    status: Signal<String>,
+    on_add_files: impl FnMut(FormEvent) + 'static,
+    on_add_folder: impl FnMut(MouseEvent) + 'static,
+    on_run: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Species identification" }
+            p {
+                "Query a local sketch database, built once with "
+                code { "fasterx sketch -o db/genomes.json genome1.fasta genome2.fasta ..." }
+                ". Containment is the fraction of a reference sketch found among all k-mers of a file."
+            }
+            div {
+                class: "controls-row",
+                if cfg!(not(target_arch = "wasm32")) {
+                    button {
+                        class: "usercontrols",
+                        onclick: on_add_folder,
+                        "Open database folder"
+                    }
+                }
+                label {
+                    r#for: "dbreader",
+                    class: "usercontrols",
+                    "Add sketch files"
+                }
+                input {
+                    id: "dbreader",
+                    class: "file-input-hidden",
+                    r#type: "file",
+                    accept: ".json",
+                    multiple: true,
+                    onchange: on_add_files
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: db.read().is_empty(),
+                    onclick: move |_| db.set(Default::default()),
+                    "Clear database"
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: db.read().is_empty(),
+                    onclick: on_run,
+                    "Identify"
+                }
+            }
+            p { "{db.read().refs.len()} reference sketches loaded. {status}" }
+            if !results.read().is_empty() {
+                table {
+                    class: "index-table",
+                    thead {
+                        tr {
+                            th { class: "histogram-header", "File" }
+                            th { class: "histogram-header", "Reference" }
+                            th { "Shared hashes" }
+                            th { "Containment %" }
+                            th { "Identity %" }
+                        }
+                    }
+                    tbody {
+                        for (file, matches) in results.read().iter() {
+                            if matches.is_empty() {
+                                tr {
+                                    td { class: "filename-cell", "{file}" }
+                                    td { class: "filename-cell", "no match" }
+                                    td {  }
+                                    td {  }
+                                    td {  }
+                                }
+                            }
+                            for (i, m) in matches.iter().enumerate() {
+                                tr {
+                                    class: if i > 0 { "member-row" } else { "" },
+                                    td { class: "filename-cell", if i == 0 { "{file}" } }
+                                    td { class: "filename-cell", "{m.reference}" }
+                                    td { "{m.shared}/{m.size}" }
+                                    td { {format!("{:.2}", m.containment() * 100.0)} }
+                                    td { {format!("{:.2}", m.identity() * 100.0)} }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// full-size plots of one file; dragging across a length plot zooms both length plots
+pub fn chart_panel(
+    files: Signal<Vec<crate::UploadedFile>>,
+    mut selected: Signal<String>,
+    mut zoom: Signal<Option<(f64, f64)>>,
+    brush: Signal<Option<(f64, f64)>>,
+    readout: Signal<String>,
+    length_bins: crate::bins::LengthBins,
+    plot_type: String,
+) -> Element {
+    let files = files.read();
+    let Some(f) = files.iter().find(|f| f.name == *selected.read()).or(files.first()) else {
+        return rsx! {};
+    };
+    let lengths = [charts::Input { name: &f.basename, data: &f.l_vector }];
+    let length = charts::length_chart(&lengths, &length_bins, &plot_type, zoom(), false);
+    let yields = charts::yield_chart(&lengths, length_bins.log, zoom(), false);
+    let quality = charts::quality_chart(&[charts::Input { name: &f.basename, data: &f.q_vector }], false);
+    let mut pooled = crate::yields::YieldMatrix::default();
+    for g in files.iter() {
+        pooled.merge(&g.yields);
+    }
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Plots" }
+            div {
+                class: "controls-row",
+                select {
+                    class: "usercontrols",
+                    value: "{f.name}",
+                    oninput: move |ev| {
+                        selected.set(ev.value());
+                        zoom.set(None);
+                    },
+                    for g in files.iter() {
+                        option { value: "{g.name}", "{g.basename}" }
+                    }
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: zoom().is_none(),
+                    onclick: move |_| zoom.set(None),
+                    "Reset zoom"
+                }
+            }
+            p { "Drag across a length plot to zoom in, double-click to reset. Hover for values." }
+            div {
+                class: "chart-row",
+                {chart_view(length, true, zoom, brush, readout)}
+                {chart_view(yields, true, zoom, brush, readout)}
+                {chart_view(quality, false, zoom, brush, readout)}
+                div { dangerous_inner_html: charts::density_svg(&f.density) }
+            }
+            p { class: "chart-readout", "{readout}" }
+            h5 { "Yield by minimum read length and mean quality" }
+            div { dangerous_inner_html: f.yields.to_html() }
+            if files.len() > 1 {
+                h5 { "All {files.len()} files" }
+                div { dangerous_inner_html: pooled.to_html() }
+            }
+        }
+    }
+}
+
+// every metric and full-size plot of one table row, stepping through the rows in table order
+#[allow(clippy::too_many_arguments)]
+pub fn detail_panel(
+    rows: Memo<Vec<String>>, // row names in table order
+    files: Signal<Vec<crate::UploadedFile>>,
+    groups: Memo<Vec<crate::groups::Group>>,
+    mut detail: Signal<Option<String>>,
+    mut zoom: Signal<Option<(f64, f64)>>,
+    brush: Signal<Option<(f64, f64)>>,
+    readout: Signal<String>,
+    length_bins: crate::bins::LengthBins,
+    plot_type: String,
+    samplesheet: Signal<String>,
+    screen_refs: &[String],
+    on_export: impl FnMut(MouseEvent) + 'static,
+) -> Element {
+    let rows = rows.read();
+    let (files, groups) = (files.read(), groups.read());
+    let Some(index) = rows.iter().position(|name| Some(name) == detail.read().as_ref()) else {
+        return rsx! {};
+    };
+    let Some(f) = crate::groups::find_row(&files, &groups, &rows[index]) else {
+        return rsx! {};
+    };
+    let previous = index.checked_sub(1).map(|i| rows[i].clone());
+    let next = rows.get(index + 1).cloned();
+    let samples = crate::indexes::parse_samplesheet(&samplesheet.read());
+    let charts = crate::detail::charts(f, &length_bins, &plot_type, zoom());
+    rsx! {
+        div {
+            class: "panel",
+            div {
+                class: "controls-row",
+                button {
+                    class: "usercontrols",
+                    onclick: move |_| detail.set(None),
+                    "Back to table"
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: previous.is_none(),
+                    onclick: move |_| {
+                        if let Some(name) = previous.clone() {
+                            detail.set(Some(name));
+                            zoom.set(None);
+                        }
+                    },
+                    "‹ Previous"
+                }
+                span { "{index + 1} of {rows.len()}" }
+                button {
+                    class: "usercontrols",
+                    disabled: next.is_none(),
+                    onclick: move |_| {
+                        if let Some(name) = next.clone() {
+                            detail.set(Some(name));
+                            zoom.set(None);
+                        }
+                    },
+                    "Next ›"
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: zoom().is_none(),
+                    onclick: move |_| zoom.set(None),
+                    "Reset zoom"
+                }
+                button {
+                    class: "usercontrols",
+                    onclick: on_export,
+                    "Export HTML"
+                }
+            }
+            h4 { "{f.basename}" }
+            table {
+                class: "index-table detail-table",
+                tbody {
+                    for (label, value) in crate::detail::metrics(f, screen_refs) {
+                        tr {
+                            th { "{label}" }
+                            td { class: "filename-cell", "{value}" }
+                        }
+                    }
+                }
+            }
+            p { "Drag across a length plot to zoom in, double-click to reset. Hover for values." }
+            div {
+                class: "chart-row",
+                for (i, chart) in charts.into_iter().enumerate() {
+                    {chart_view(chart, i < 2, zoom, brush, readout)}
+                }
+                div { dangerous_inner_html: charts::density_svg(&f.density) }
+            }
+            p { class: "chart-readout", "{readout}" }
+            h5 { "Yield by minimum read length and mean quality" }
+            div { dangerous_inner_html: f.yields.to_html() }
+            if !f.index_counts.is_empty() {
+                {index_table(f, &samples)}
+            }
+        }
+    }
+}
+
+// an SVG chart under a transparent overlay that tracks the pointer in plot coordinates
+fn chart_view(
+    chart: charts::Chart,
+    zoomable: bool,
+    mut zoom: Signal<Option<(f64, f64)>>,
+    mut brush: Signal<Option<(f64, f64)>>,
+    mut readout: Signal<String>,
+) -> Element {
+    let svg = chart.to_svg();
+    let hover = chart.clone();
+    let selection = brush().filter(|_| zoomable).map(|(a, b)| (a.min(b), (a - b).abs()));
+    rsx! {
+        div {
+            class: "chart-box",
+            div { dangerous_inner_html: svg }
+            div {
+                class: if zoomable { "chart-overlay" } else { "chart-overlay static" },
+                style: "left: {charts::LEFT}px; top: {charts::TOP}px; width: {charts::Chart::plot_width()}px; height: {charts::Chart::plot_height()}px;",
+                onmousedown: move |ev| {
+                    if zoomable {
+                        let x = ev.element_coordinates().x;
+                        brush.set(Some((x, x)));
+                    }
+                },
+                onmousemove: move |ev| {
+                    let x = ev.element_coordinates().x;
+                    if let Some((start, _)) = brush().filter(|_| zoomable) {
+                        brush.set(Some((start, x)));
+                    }
+                    readout.set(hover.readout(hover.x_at(x)).unwrap_or_default());
+                },
+                onmouseup: move |_| {
+                    if let Some((a, b)) = brush().filter(|_| zoomable) {
+                        if (a - b).abs() > 4.0 {
+                            zoom.set(Some((chart.x_at(a.min(b)), chart.x_at(a.max(b)))));
+                        }
+                    }
+                    brush.set(None);
+                },
+                onmouseleave: move |_| brush.set(None),
+                ondoubleclick: move |_| {
+                    if zoomable {
+                        zoom.set(None);
+                    }
+                },
+                if let Some((left, width)) = selection {
+                    div {
+                        class: "chart-brush",
+                        style: "left: {left}px; width: {width}px;",
+                    }
+                }
+            }
+        }
+    }
+}
+
+// length, quality and yield of the ticked rows overlaid in one chart each
+#[allow(clippy::too_many_arguments)]
+pub fn compare_panel(
+    files: Memo<Vec<crate::UploadedFile>>,
+    mut selected: Signal<std::collections::BTreeSet<String>>,
+    mut normalise: Signal<bool>,
+    mut zoom: Signal<Option<(f64, f64)>>,
+    brush: Signal<Option<(f64, f64)>>,
+    readout: Signal<String>,
+    length_bins: crate::bins::LengthBins,
+    plot_type: String,
+) -> Element {
+    let files = files.read();
+    let lengths: Vec<charts::Input<i64>> = files.iter().map(|f| charts::Input { name: &f.basename, data: &f.l_vector }).collect();
+    let qualities: Vec<charts::Input<u8>> = files.iter().map(|f| charts::Input { name: &f.basename, data: &f.q_vector }).collect();
+    let length = charts::length_chart(&lengths, &length_bins, &plot_type, zoom(), normalise());
+    let yields = charts::yield_chart(&lengths, length_bins.log, zoom(), normalise());
+    let quality = charts::quality_chart(&qualities, normalise());
+    rsx! {
+        div {
+            class: "panel",
+            h4 { "Comparison of {files.len()} files" }
+            div {
+                class: "controls-row",
+                label {
+                    class: "field-label",
+                    input {
+                        r#type: "checkbox",
+                        checked: normalise(),
+                        onchange: move |_| normalise.set(!normalise()),
+                    }
+                    "Normalise to % of each file"
+                }
+                button {
+                    class: "usercontrols",
+                    disabled: zoom().is_none(),
+                    onclick: move |_| zoom.set(None),
+                    "Reset zoom"
+                }
+                button {
+                    class: "usercontrols",
+                    onclick: move |_| selected.set(Default::default()),
+                    "Clear selection"
+                }
+            }
+            p { "Drag across a length plot to zoom in, double-click to reset. Hover for values." }
+            div {
+                class: "chart-row",
+                {chart_view(length, true, zoom, brush, readout)}
+                {chart_view(yields, true, zoom, brush, readout)}
+                {chart_view(quality, false, zoom, brush, readout)}
+            }
+            p { class: "chart-readout", "{readout}" }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// Read length x mean read quality density, binned on log10 length and whole Q values
+
+pub const LENGTH_BINS: usize = 48; // 8 per decade, 1 bp to 1 Mbp
+pub const DECADES: f64 = 6.0;
+
+// counts[q * LENGTH_BINS + length bin], with rows up to the highest Q seen, so that
+// HiFi and duplex reads keep their own rows
+#[derive(Clone, Default, PartialEq)]
+pub struct Density {
+    counts: Vec<u64>,
+}
+
+impl Density {
+    pub fn length_bin(length: usize) -> usize {
+        let bin = (length.max(1) as f64).log10() / DECADES * LENGTH_BINS as f64;
+        (bin as usize).min(LENGTH_BINS - 1)
+    }
+
+    // lower edge of a length bin in bp
+    pub fn length_start(bin: usize) -> f64 {
+        10f64.powf(bin as f64 * DECADES / LENGTH_BINS as f64)
+    }
+
+    // Q rows held
+    fn rows(&self) -> usize {
+        self.counts.len() / LENGTH_BINS
+    }
+
+    pub fn add(&mut self, length: usize, qscore: u8) {
+        let q = qscore as usize;
+        if q >= self.rows() {
+            self.counts.resize((q + 1) * LENGTH_BINS, 0);
+        }
+        self.counts[q * LENGTH_BINS + Self::length_bin(length)] += 1;
+    }
+
+    pub fn merge(&mut self, other: &Density) {
+        if other.counts.len() > self.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (a, b) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *a += b;
+        }
+    }
+
+    pub fn get(&self, length_bin: usize, q: usize) -> u64 {
+        self.counts.get(q * LENGTH_BINS + length_bin).copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> u64 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // (first, last) length bins and highest Q row holding any reads
+    pub fn extent(&self) -> Option<(usize, usize, usize)> {
+        let mut extent: Option<(usize, usize, usize)> = None;
+        for q in 0..self.rows() {
+            for bin in 0..LENGTH_BINS {
+                if self.get(bin, q) > 0 {
+                    extent = Some(match extent {
+                        Some((lo, hi, top)) => (lo.min(bin), hi.max(bin), top.max(q)),
+                        None => (bin, bin, q),
+                    });
+                }
+            }
+        }
+        extent
+    }
+}
@@ -0,0 +1,103 @@
+// One file in full: every metric, full-size plots and the header-derived tables,
+// shown on the detail page and exported as a page of its own
+
+use indicatif::HumanCount;
+
+use crate::bins::LengthBins;
+use crate::metrics::{self, Metric};
+use crate::{charts, indexes, UploadedFile};
+
+// label and value of every metric of a file, in display order
+pub fn metrics(f: &UploadedFile, screen_refs: &[String]) -> Vec<(String, String)> {
+    let shortest = f.l_vector.iter().copied().min().unwrap_or(0);
+    let longest = f.l_vector.iter().copied().max().unwrap_or(0);
+    let mean_length = (f.reads > 0).then(|| f.bases as f64 / f.reads as f64);
+    let mean_q = (!f.q_hash.is_empty()).then(|| f.q_vector.iter().map(|&q| q as f64).sum::<f64>() / f.q_vector.len() as f64);
+    let show = |m: Metric| m.display("comma");
+
+    let mut list = vec![
+        ("File".to_string(), f.name.clone()),
+        ("Platform".to_string(), f.platform.clone()),
+        ("Reads".to_string(), show(Metric::count(f.reads))),
+        ("Bases".to_string(), show(Metric::count(f.bases))),
+        ("N50".to_string(), show(Metric::count(f.nx))),
+        ("Shortest read".to_string(), show(Metric::count(shortest as u64))),
+        ("Longest read".to_string(), show(Metric::count(longest as u64))),
+        ("Mean read length".to_string(), show(Metric::number(mean_length))),
+        ("GC%".to_string(), show(f.gc)),
+        ("Q20%".to_string(), show(f.q20)),
+        ("Q30%".to_string(), show(f.q30)),
+        ("Mean read Q".to_string(), show(Metric::qscore(mean_q, 1))),
+        ("Median read Q".to_string(), show(metrics::column(f, "m_qscore"))),
+    ];
+    if f.interleaved {
+        list.push(("Interleaved".to_string(), "R1/R2 alternate".to_string()));
+    }
+    if f.platform == "PacBio" {
+        list.push(("Mean passes".to_string(), show(metrics::column(f, "passes"))));
+        list.push(("rq Q20%".to_string(), show(metrics::column(f, "rq_q20"))));
+        list.push(("rq Q30%".to_string(), show(metrics::column(f, "rq_q30"))));
+    }
+    if !f.index_counts.is_empty() {
+        let indexed: u64 = f.index_counts.values().sum();
+        list.push(("Indexed reads".to_string(), show(Metric::count(indexed))));
+        list.push(("Distinct barcodes".to_string(), show(Metric::count(f.index_counts.len() as u64))));
+    }
+    for (i, name) in screen_refs.iter().enumerate() {
+        list.push((format!("{} %", name), show(Metric::from_percent(f.screen.percent(i)))));
+    }
+    list
+}
+
+// length, yield, read quality, and quality and composition along the reads
+pub fn charts(f: &UploadedFile, length_bins: &LengthBins, plot_type: &str, zoom: Option<(f64, f64)>) -> Vec<charts::Chart> {
+    let lengths = [charts::Input { name: &f.basename, data: &f.l_vector }];
+    vec![
+        charts::length_chart(&lengths, length_bins, plot_type, zoom, false),
+        charts::yield_chart(&lengths, length_bins.log, zoom, false),
+        charts::quality_chart(&[charts::Input { name: &f.basename, data: &f.q_vector }], false),
+        charts::position_quality_chart(&f.basename, &f.positions),
+        charts::composition_chart(&f.positions),
+    ]
+}
+
+// the detail page as a standalone HTML file
+pub fn to_html(f: &UploadedFile, length_bins: &LengthBins, plot_type: &str, screen_refs: &[String]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n");
+    html.push_str(&format!("<title>{} - fasterX</title>\n<style>\n", charts::escape(&f.basename)));
+    html.push_str(include_str!("../assets/custom.css"));
+    html.push_str("</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h2 id='title'>{}</h2>\n", charts::escape(&f.basename)));
+    html.push_str(&format!("<p>Generated on: {}</p>\n", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")));
+
+    html.push_str("<table class='index-table detail-table'>\n<tbody>\n");
+    for (label, value) in metrics(f, screen_refs) {
+        html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", charts::escape(&label), charts::escape(&value)));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    html.push_str("<h3>Plots</h3>\n<div class='chart-row'>\n");
+    for chart in charts(f, length_bins, plot_type, None) {
+        html.push_str(&chart.to_svg());
+        html.push('\n');
+    }
+    html.push_str(&charts::density_svg(&f.density));
+    html.push_str("</div>\n<h4>Yield by minimum read length and mean quality</h4>\n");
+    html.push_str(&f.yields.to_html());
+
+    if !f.index_counts.is_empty() {
+        let indexed: u64 = f.index_counts.values().sum();
+        html.push_str("<h4>Top index barcodes</h4>\n<table class='index-table'>\n<thead><tr><th>Barcode</th><th>Reads</th><th>%</th></tr></thead>\n<tbody>\n");
+        for (barcode, count) in indexes::top_barcodes(&f.index_counts, 20) {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.2}</td></tr>\n",
+                barcode,
+                HumanCount(count),
+                count as f64 / indexed as f64 * 100.0
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+    html.push_str("</body>\n</html>");
+    html
+}
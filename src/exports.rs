@@ -0,0 +1,135 @@
+// Writing selected reads to new gzipped FASTQ files, with statistics of what was written
+
+use std::io::Write;
+
+use bio::io::fastq;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::filter::ReadFilter;
+use crate::stats::FileStats;
+use crate::subsample::{SamplePlan, Subsample};
+use crate::UploadedFile;
+
+// how reads are chosen for an export
+#[derive(Clone, PartialEq)]
+pub enum ReadSelector {
+    Filter(ReadFilter),
+    Subsample(Subsample),
+}
+
+// the selector applied to the records of one file
+pub enum FileSelector {
+    Filter(ReadFilter),
+    Sample(SamplePlan),
+}
+
+impl ReadSelector {
+    pub fn for_file(&self, f: &UploadedFile) -> FileSelector {
+        match self {
+            ReadSelector::Filter(filter) => FileSelector::Filter(filter.clone()),
+            ReadSelector::Subsample(subsample) => FileSelector::Sample(subsample.plan(f)),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ReadSelector::Filter(filter) => filter.describe(),
+            ReadSelector::Subsample(subsample) => subsample.describe(),
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            ReadSelector::Filter(_) => "filtered",
+            ReadSelector::Subsample(_) => "subsampled",
+        }
+    }
+}
+
+impl FileSelector {
+    pub fn select(&mut self, rec: &fastq::Record) -> Option<fastq::Record> {
+        match self {
+            FileSelector::Filter(filter) => filter.apply(rec),
+            FileSelector::Sample(plan) => plan.select(rec),
+        }
+    }
+}
+
+pub struct FastqOutput {
+    encoder: GzEncoder<Vec<u8>>,
+    stats: FileStats,
+}
+
+impl FastqOutput {
+    pub fn new(name: &str) -> Self {
+        FastqOutput {
+            encoder: GzEncoder::new(Vec::new(), Compression::default()),
+            stats: FileStats::new(name),
+        }
+    }
+
+    // records read from fasta have no qualities, these are written as Q0
+    pub fn write(&mut self, rec: &fastq::Record) -> std::io::Result<()> {
+        self.stats.add(rec);
+        let e = &mut self.encoder;
+        match rec.desc() {
+            Some(desc) => writeln!(e, "@{} {}", rec.id(), desc)?,
+            None => writeln!(e, "@{}", rec.id())?,
+        }
+        e.write_all(rec.seq())?;
+        e.write_all(b"\n+\n")?;
+        if rec.qual().len() == rec.seq().len() {
+            e.write_all(rec.qual())?;
+        } else {
+            e.write_all(&vec![b'!'; rec.seq().len()])?;
+        }
+        e.write_all(b"\n")
+    }
+
+    pub fn finish(self) -> std::io::Result<(Vec<u8>, UploadedFile)> {
+        Ok((self.encoder.finish()?, self.stats.finish()))
+    }
+}
+
+// "run1/reads.fastq.gz" -> "reads.filtered.fastq.gz"
+pub fn output_name(input: &str, suffix: &str) -> String {
+    format!("{}.{}.fastq.gz", crate::sources::file_stem(input), suffix)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_to_dir(dir: &std::path::Path, name: &str, bytes: &[u8]) -> std::io::Result<String> {
+    let path = dir.join(name);
+    std::fs::write(&path, bytes)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// offer bytes as a file download in the browser
+#[cfg(target_arch = "wasm32")]
+pub fn download(name: &str, bytes: &[u8], mime: &str) {
+    use wasm_bindgen::JsCast;
+
+    let window = web_sys::window().expect("window not found");
+    let document = window.document().expect("document not found");
+
+    // Create blob
+    let parts = js_sys::Array::new();
+    parts.push(&js_sys::Uint8Array::from(bytes));
+    let blob_props = web_sys::BlobPropertyBag::new();
+    blob_props.set_type(mime);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &blob_props).expect("failed to create blob");
+
+    // Create URL
+    let url = web_sys::Url::create_object_url_with_blob(&blob).expect("failed to create URL");
+
+    // Create anchor and click it
+    let a = document.create_element("a").expect("failed to create anchor").dyn_into::<web_sys::HtmlAnchorElement>().expect("failed to cast anchor");
+    a.set_href(&url);
+    a.set_download(name);
+    document.body().expect("body not found").append_child(&a).expect("failed to append anchor");
+    a.click();
+
+    // Cleanup
+    document.body().unwrap().remove_child(&a).expect("failed to remove anchor");
+    web_sys::Url::revoke_object_url(&url).expect("failed to revoke URL");
+}
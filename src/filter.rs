@@ -0,0 +1,62 @@
+// Read filtering by length, mean quality and N content, with optional head/tail trimming
+
+use bio::io::fastq;
+
+use crate::modules;
+
+#[derive(Clone, PartialEq)]
+pub struct ReadFilter {
+    pub min_length: usize,
+    pub max_length: usize, // 0 means no limit
+    pub min_qscore: u8,
+    pub max_n_percent: f64,
+    pub trim_head: usize,
+    pub trim_tail: usize,
+}
+
+impl Default for ReadFilter {
+    fn default() -> Self {
+        ReadFilter {
+            min_length: 1000,
+            max_length: 0,
+            min_qscore: 10,
+            max_n_percent: 100.0,
+            trim_head: 0,
+            trim_tail: 0,
+        }
+    }
+}
+
+impl ReadFilter {
+    // the trimmed record if it passes, reads are trimmed first and then filtered
+    pub fn apply(&self, rec: &fastq::Record) -> Option<fastq::Record> {
+        let len = rec.seq().len();
+        if self.trim_head + self.trim_tail >= len {
+            return None;
+        }
+        let (start, end) = (self.trim_head, len - self.trim_tail);
+        let seq = &rec.seq()[start..end];
+        let qual = if rec.qual().len() == len { &rec.qual()[start..end] } else { rec.qual() };
+
+        if seq.len() < self.min_length || (self.max_length > 0 && seq.len() > self.max_length) {
+            return None;
+        }
+        // fasta reads have no qualities to test
+        if self.min_qscore > 0 && !qual.is_empty() && modules::qscore_mean(qual) < self.min_qscore {
+            return None;
+        }
+        let n_bases = seq.iter().filter(|&&b| b == b'N' || b == b'n').count();
+        if n_bases as f64 / seq.len() as f64 * 100.0 > self.max_n_percent {
+            return None;
+        }
+        Some(fastq::Record::with_attrs(rec.id(), rec.desc(), seq, qual))
+    }
+
+    pub fn describe(&self) -> String {
+        let max = if self.max_length > 0 { format!("-{}", self.max_length) } else { "+".to_string() };
+        format!(
+            "length {}{} bp, mean Q >= {}, N <= {}%, trim {}/{} bp",
+            self.min_length, max, self.min_qscore, self.max_n_percent, self.trim_head, self.trim_tail
+        )
+    }
+}
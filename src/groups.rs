@@ -0,0 +1,346 @@
+// Pooling files into samples, e.g. R1/R2 mates
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::headers;
+use crate::metrics::Metric;
+use crate::modules;
+use crate::screen::ScreenCounts;
+use crate::density::Density;
+use crate::positions::Positions;
+use crate::yields::YieldMatrix;
+use crate::UploadedFile;
+
+#[derive(Clone, PartialEq)]
+pub struct Group {
+    pub pooled: UploadedFile,
+    pub members: Vec<UploadedFile>,
+    pub note: String, // e.g. result of the pairing check
+    pub inserts: Vec<i64>, // insert sizes of overlapping pairs, paired groups only
+}
+
+// read pairs used for insert size estimation
+pub const INSERT_SAMPLE: usize = 10_000;
+// files with longer reads are not sampled, paired reads are short
+pub const INSERT_READ_LENGTH: usize = 1000;
+// sampled read sequences of one file
+pub type ReadSample = Vec<Vec<u8>>;
+
+// group loaded files according to mode ("none", "pairs", "folder", "barcode" or "regex"),
+// in pairs mode interleaved files are split into mates
+pub fn group_files(files: &[UploadedFile], mode: &str, pattern: &str) -> Vec<Group> {
+    let single = |f: &UploadedFile| Group {
+        pooled: f.clone(),
+        members: vec![f.clone()],
+        note: if f.interleaved { "interleaved".to_string() } else { String::new() },
+        inserts: Vec::new(),
+    };
+    match mode {
+        "pairs" => {}
+        "folder" | "barcode" | "regex" => return pool_by_key(files, mode, pattern),
+        _ => return files.iter().map(single).collect(),
+    }
+
+    let mut groups = Vec::new();
+    let mut mates: BTreeMap<String, Vec<(u8, &UploadedFile)>> = BTreeMap::new();
+    let keys = mate_keys(files);
+    for (f, key) in files.iter().zip(keys) {
+        if f.interleaved {
+            groups.push(Group {
+                pooled: f.clone(),
+                members: split_mates(f).to_vec(),
+                note: "interleaved".to_string(),
+                inserts: f.inserts.clone(),
+            });
+            continue;
+        }
+        match key {
+            Some((key, mate)) => mates.entry(key).or_default().push((mate, f)),
+            None => groups.push(single(f)),
+        }
+    }
+
+    for (_, mut pair) in mates {
+        if pair.len() != 2 || pair[0].0 == pair[1].0 {
+            groups.extend(pair.iter().map(|(_, f)| single(f)));
+            continue;
+        }
+        pair.sort_by_key(|(mate, _)| *mate);
+        let (r1, r2) = (pair[0].1, pair[1].1);
+        let (_, sample, _) = headers::mate_from_filename(&r1.basename).unwrap_or_default();
+        let dir = r1.name.strip_suffix(&r1.basename).unwrap_or_default();
+        groups.push(Group {
+            pooled: pool_files(&format!("{}{}", dir, sample), &sample, &[r1, r2]),
+            members: vec![r1.clone(), r2.clone()],
+            note: check_pair(r1, r2),
+            inserts: r1.inserts.clone(),
+        });
+    }
+    groups
+}
+
+// folder and file name without the mate token, and the mate number, of every R1/R2 file;
+// a bare "_1"/"_2" also numbers the chunks of a run, so such files are mates only when
+// they are the only two numbered files of that name
+fn mate_keys(files: &[UploadedFile]) -> Vec<Option<(String, u8)>> {
+    let dir = |f: &UploadedFile| f.name.strip_suffix(&f.basename).unwrap_or_default().to_string();
+    let chunk = |f: &UploadedFile| headers::numbered_file(&f.basename).map(|(name, _, ext)| format!("{}{}#{}", dir(f), name, ext));
+    let mut numbered: BTreeMap<String, usize> = BTreeMap::new();
+    for key in files.iter().filter_map(chunk) {
+        *numbered.entry(key).or_insert(0) += 1;
+    }
+    files
+        .iter()
+        .map(|f| {
+            let (key, _, mate) = headers::mate_from_filename(&f.basename)?;
+            if headers::mate_token(&f.basename).is_none() && numbered.get(&chunk(f)?) != Some(&2) {
+                return None;
+            }
+            Some((format!("{}{}", dir(f), key), mate))
+        })
+        .collect()
+}
+
+// read samples of R1/R2 files whose mate is loaded too, taken once when the pair is
+// complete, with the R1 name the insert sizes are kept on
+pub fn take_pairs(files: &mut [UploadedFile]) -> Vec<(String, ReadSample, ReadSample)> {
+    let mut mates: BTreeMap<String, Vec<(u8, usize)>> = BTreeMap::new();
+    for (i, key) in mate_keys(files).into_iter().enumerate() {
+        let f = &mut files[i];
+        if f.interleaved {
+            continue;
+        }
+        match key {
+            Some((key, mate)) => {
+                mates.entry(key).or_default().push((mate, i));
+            }
+            // numbered chunks of a run, not mates: nothing to check pairing or inserts with
+            None => {
+                f.seq_sample = Vec::new();
+                f.id_hashes = Vec::new();
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    for (_, mut pair) in mates {
+        if pair.len() != 2 || pair[0].0 == pair[1].0 {
+            continue;
+        }
+        pair.sort_by_key(|(mate, _)| *mate);
+        let (r1, r2) = (pair[0].1, pair[1].1);
+        if files[r1].seq_sample.is_empty() || files[r2].seq_sample.is_empty() {
+            continue;
+        }
+        let sample1 = std::mem::take(&mut files[r1].seq_sample);
+        let sample2 = std::mem::take(&mut files[r2].seq_sample);
+        pairs.push((files[r1].name.clone(), sample1, sample2));
+    }
+    pairs
+}
+
+pub fn valid_pattern(pattern: &str) -> bool {
+    pattern.is_empty() || Regex::new(pattern).is_ok()
+}
+
+// key a file is pooled under, None leaves the file on its own
+fn group_key(f: &UploadedFile, mode: &str, re: Option<&Regex>) -> Option<String> {
+    match mode {
+        "folder" => {
+            let dir = Path::new(&f.name).parent()?.to_string_lossy().to_string();
+            (!dir.is_empty()).then_some(dir)
+        }
+        "barcode" => {
+            // the file name, else its own folder; the token must start the name or follow a separator
+            let re = BARCODE.get_or_init(|| Regex::new(r"(?i)(?:^|[^a-z0-9])(barcode|bc|nb|rb)[-_]?(\d+)(?:\D|$)").unwrap());
+            let path = Path::new(&f.name);
+            let parent = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string());
+            let caps = re.captures(&f.basename).or_else(|| re.captures(parent.as_deref()?))?;
+            let n = caps[2].parse::<u32>().ok()?;
+            Some(format!("barcode{:02}", n))
+        }
+        "regex" => {
+            let caps = re?.captures(&f.name)?;
+            Some(caps.get(1).unwrap_or_else(|| caps.get(0).unwrap()).as_str().to_string())
+        }
+        _ => None,
+    }
+}
+
+static BARCODE: OnceLock<Regex> = OnceLock::new();
+
+fn pool_by_key(files: &[UploadedFile], mode: &str, pattern: &str) -> Vec<Group> {
+    let re = (!pattern.is_empty()).then(|| Regex::new(pattern).ok()).flatten();
+    let mut keyed: BTreeMap<String, Vec<&UploadedFile>> = BTreeMap::new();
+    let mut groups = Vec::new();
+
+    for f in files {
+        match group_key(f, mode, re.as_ref()) {
+            Some(key) => keyed.entry(key).or_default().push(f),
+            None => groups.push(Group {
+                pooled: f.clone(),
+                members: vec![f.clone()],
+                note: String::new(),
+                inserts: Vec::new(),
+            }),
+        }
+    }
+    for (key, mut members) in keyed {
+        members.sort_by(|a, b| natord::compare(&a.name, &b.name));
+        let basename = Path::new(&key).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(key.clone());
+        groups.push(Group {
+            pooled: pool_files(&key, &basename, &members),
+            members: members.into_iter().cloned().collect(),
+            note: String::new(),
+            inserts: Vec::new(),
+        });
+    }
+    groups
+}
+
+// R1 and R2 must have the same number of reads, with matching ids in the same order
+pub fn check_pair(r1: &UploadedFile, r2: &UploadedFile) -> String {
+    if r1.reads != r2.reads {
+        return format!("read count mismatch (R1 {}, R2 {})", r1.reads, r2.reads);
+    }
+    match r1.id_hashes.iter().zip(r2.id_hashes.iter()).position(|(a, b)| a != b) {
+        Some(i) => format!("read ids differ at read {}", i + 1),
+        None => "pairs consistent".to_string(),
+    }
+}
+
+// combined statistics for several files, as if they were one
+pub fn pool_files(name: &str, basename: &str, files: &[&UploadedFile]) -> UploadedFile {
+    let mut l_vector: Vec<i64> = Vec::new();
+    let mut q_vector: Vec<u8> = Vec::new();
+    let mut q_hash: BTreeMap<u8, i64> = BTreeMap::new();
+    let mut index_counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut hifi = headers::HifiStats::default();
+    let mut screen = ScreenCounts::default();
+    let mut density = Density::default();
+    let mut yields = YieldMatrix::default();
+    let mut positions = Positions::default();
+    let mut gc_bases = 0;
+
+    for f in files {
+        l_vector.extend_from_slice(&f.l_vector);
+        q_vector.extend_from_slice(&f.q_vector);
+        gc_bases += f.gc_bases;
+        for (&q, &n) in &f.q_hash {
+            *q_hash.entry(q).or_insert(0) += n;
+        }
+        for (index, &n) in &f.index_counts {
+            *index_counts.entry(index.clone()).or_insert(0) += n;
+        }
+        hifi.merge(&f.hifi);
+        screen.merge(&f.screen);
+        density.merge(&f.density);
+        yields.merge(&f.yields);
+        positions.merge(&f.positions);
+    }
+
+    let reads = files.iter().map(|f| f.reads).sum::<u64>();
+    let bases = files.iter().map(|f| f.bases).sum::<u64>();
+    let qual_bases = |min: u8| q_hash.range(min..).map(|(_, &n)| n).sum::<i64>();
+    // pooled reads have no order to keep, so the vectors are sorted in place
+    let nx = if l_vector.is_empty() { 0 } else { modules::get_nx(&mut l_vector, 0.5) as u64 };
+    let m_qscore = if q_vector.is_empty() { 0 } else { modules::median(&mut q_vector) };
+    let mut platforms: Vec<&str> = files.iter().map(|f| f.platform.as_str()).collect();
+    platforms.sort_unstable();
+    platforms.dedup();
+
+    UploadedFile {
+        name: name.to_string(),
+        basename: basename.to_string(),
+        reads,
+        bases,
+        nx,
+        gc: Metric::percent(gc_bases as f64, bases as f64),
+        q20: Metric::percent(qual_bases(53) as f64, qual_bases(0) as f64), // 33 offset
+        q30: Metric::percent(qual_bases(63) as f64, qual_bases(0) as f64),
+        m_qscore,
+        l_vector,
+        q_vector,
+        q_hash,
+        gc_bases,
+        index_counts,
+        platform: if platforms.len() == 1 { platforms[0].to_string() } else { "mixed".to_string() },
+        hifi,
+        id_hashes: Vec::new(),
+        interleaved: false,
+        mate_gc_bases: [0; 2],
+        mate_q_hash: Default::default(),
+        seq_sample: Vec::new(),
+        inserts: Vec::new(),
+        screen,
+        density,
+        yields,
+        positions,
+    }
+}
+
+// a table row by name: a loaded file or a pooled group
+pub fn find_row<'a>(files: &'a [UploadedFile], groups: &'a [Group], name: &str) -> Option<&'a UploadedFile> {
+    files.iter().chain(groups.iter().map(|g| &g.pooled)).find(|f| f.name == name)
+}
+
+// R1 and R2 statistics of an interleaved file; reads alternate, so mates are split by record parity
+pub fn split_mates(f: &UploadedFile) -> [UploadedFile; 2] {
+    [0, 1].map(|mate| {
+        let mut l_vector: Vec<i64> = f.l_vector.iter().skip(mate).step_by(2).copied().collect();
+        let mut q_vector: Vec<u8> = f.q_vector.iter().skip(mate).step_by(2).copied().collect();
+        let q_hash = f.mate_q_hash[mate].clone();
+        let reads = l_vector.len() as u64;
+        let bases = l_vector.iter().sum::<i64>() as u64;
+        let nx = if l_vector.is_empty() { 0 } else { modules::get_nx(&mut l_vector, 0.5) as u64 };
+        let m_qscore = if q_vector.is_empty() { 0 } else { modules::median(&mut q_vector) };
+        let qual_bases = |min: u8| q_hash.range(min..).map(|(_, &n)| n).sum::<i64>();
+        let gc_bases = f.mate_gc_bases[mate];
+
+        UploadedFile {
+            name: format!("{} R{}", f.name, mate + 1),
+            basename: format!("{} R{}", f.basename, mate + 1),
+            reads,
+            bases,
+            nx,
+            gc: Metric::percent(gc_bases as f64, bases as f64),
+            q20: Metric::percent(qual_bases(53) as f64, qual_bases(0) as f64), // 33 offset
+            q30: Metric::percent(qual_bases(63) as f64, qual_bases(0) as f64),
+            m_qscore,
+            l_vector,
+            q_vector,
+            q_hash,
+            gc_bases,
+            index_counts: BTreeMap::new(),
+            platform: f.platform.clone(),
+            hifi: headers::HifiStats::default(),
+            id_hashes: Vec::new(),
+            interleaved: false,
+            mate_gc_bases: [0; 2],
+            mate_q_hash: Default::default(),
+            seq_sample: Vec::new(),
+            inserts: Vec::new(),
+            screen: ScreenCounts::default(),
+            density: Density::default(),
+            yields: YieldMatrix::default(),
+            positions: Positions::default(),
+        }
+    })
+}
+
+// insert sizes from the overlapping pairs among the sampled reads
+pub fn insert_sizes<'a>(pairs: impl Iterator<Item = (&'a Vec<u8>, &'a Vec<u8>)>) -> Vec<i64> {
+    pairs
+        .filter_map(|(r1, r2)| modules::insert_size(r1, r2, 15))
+        .map(|insert| insert as i64)
+        .collect()
+}
+
+// bin size for insert histograms, 30 bins of a multiple of 10 bp covering the longest insert
+pub fn insert_binsize(inserts: &[i64]) -> usize {
+    let max = inserts.iter().copied().max().unwrap_or(0) as usize;
+    (max / 30 / 10 + 1) * 10
+}
@@ -0,0 +1,228 @@
+// Helpers for information encoded in read names and descriptions
+
+// index sequence(s) from an Illumina (Casava 1.8+) comment, e.g. "1:N:0:ACGTACGT+TTGGCCAA"
+// returns None for other platforms or when the comment holds a sample number instead
+pub fn illumina_index(desc: Option<&str>) -> Option<&str> {
+    let field = desc?.split_whitespace().next()?;
+    let mut parts = field.splitn(4, ':');
+    let mate = parts.next()?;
+    let filtered = parts.next()?;
+    let _control = parts.next()?;
+    let index = parts.next()?;
+
+    if !matches!(mate, "1" | "2" | "3" | "4") || !matches!(filtered, "Y" | "N") {
+        return None;
+    }
+    if index.is_empty() || !index.bytes().all(|b| matches!(b, b'A' | b'C' | b'G' | b'T' | b'N' | b'+')) {
+        return None;
+    }
+    Some(index)
+}
+
+// sequencing platform guessed from the first read header
+pub fn detect_platform(id: &str, desc: Option<&str>) -> &'static str {
+    if pacbio_zmw(id).is_some() {
+        return "PacBio";
+    }
+    if illumina_index(desc).is_some() || desc.is_some_and(|d| d.starts_with("1:N:") || d.starts_with("2:N:")) || id.split(':').count() >= 7 {
+        return "Illumina";
+    }
+    if desc.is_some_and(|d| d.contains("runid=") || d.contains("ch=") || d.contains("start_time=")) {
+        return "ONT";
+    }
+    "unknown"
+}
+
+// movie name and ZMW hole number from a PacBio read name, e.g. "m64011_190830_220126/1/ccs"
+pub fn pacbio_zmw(id: &str) -> Option<(&str, u64)> {
+    let mut parts = id.split('/');
+    let movie = parts.next()?;
+    let zmw = parts.next()?.parse::<u64>().ok()?;
+    if movie.starts_with('m') && movie.contains('_') {
+        Some((movie, zmw))
+    } else {
+        None
+    }
+}
+
+// number of passes (np) and read quality (rq) tags, as "np=12" or SAM style "np:i:12"
+pub fn hifi_tags(desc: Option<&str>) -> (Option<u32>, Option<f32>) {
+    let mut np = None;
+    let mut rq = None;
+    for tag in desc.unwrap_or_default().split_whitespace() {
+        let value = |key: &str, sam: &str| tag.strip_prefix(key).or_else(|| tag.strip_prefix(sam));
+        if let Some(v) = value("np=", "np:i:") {
+            np = v.parse().ok();
+        } else if let Some(v) = value("rq=", "rq:f:") {
+            rq = v.parse().ok();
+        }
+    }
+    (np, rq)
+}
+
+// PacBio HiFi specific counters, filled only where the tags are present
+#[derive(Clone, Default, PartialEq)]
+pub struct HifiStats {
+    pub movies: std::collections::BTreeSet<String>,
+    pub zmw_reads: u64, // reads named by movie/ZMW, a ZMW can give several (e.g. by-strand)
+    pub np_reads: u64,
+    pub np_sum: u64,
+    pub rq_reads: u64,
+    pub rq_q20: u64,
+    pub rq_q30: u64,
+}
+
+impl HifiStats {
+    pub fn add(&mut self, id: &str, desc: Option<&str>) {
+        if let Some((movie, _zmw)) = pacbio_zmw(id) {
+            self.zmw_reads += 1;
+            if !self.movies.contains(movie) {
+                self.movies.insert(movie.to_string());
+            }
+        }
+        let (np, rq) = hifi_tags(desc);
+        if let Some(np) = np {
+            self.np_reads += 1;
+            self.np_sum += np as u64;
+        }
+        if let Some(rq) = rq {
+            self.rq_reads += 1;
+            // rq 0.99 is Q20, 0.999 is Q30
+            if rq >= 0.99 {
+                self.rq_q20 += 1;
+            }
+            if rq >= 0.999 {
+                self.rq_q30 += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &HifiStats) {
+        self.movies.extend(other.movies.iter().cloned());
+        self.zmw_reads += other.zmw_reads;
+        self.np_reads += other.np_reads;
+        self.np_sum += other.np_sum;
+        self.rq_reads += other.rq_reads;
+        self.rq_q20 += other.rq_q20;
+        self.rq_q30 += other.rq_q30;
+    }
+
+    pub fn mean_passes(&self) -> Option<f64> {
+        (self.np_reads > 0).then(|| self.np_sum as f64 / self.np_reads as f64)
+    }
+
+    pub fn rq_q20_percent(&self) -> Option<f64> {
+        (self.rq_reads > 0).then(|| self.rq_q20 as f64 / self.rq_reads as f64 * 100.0)
+    }
+
+    pub fn rq_q30_percent(&self) -> Option<f64> {
+        (self.rq_reads > 0).then(|| self.rq_q30 as f64 / self.rq_reads as f64 * 100.0)
+    }
+}
+
+// sample key and mate number from paired file names such as
+// "sample_S1_L001_R1_001.fastq.gz", "sample_R2.fq.gz" or "sample_1.fastq"
+// the key keeps what follows the mate token so that chunked files pair correctly
+pub fn mate_from_filename(basename: &str) -> Option<(String, String, u8)> {
+    if let Some(found) = mate_token(basename) {
+        return Some(found);
+    }
+    // a bare "_1"/"_2" only right before the extension
+    match numbered_file(basename)? {
+        (sample, "1", ext) => Some((format!("{}#{}", sample, ext), sample.to_string(), 1)),
+        (sample, "2", ext) => Some((format!("{}#{}", sample, ext), sample.to_string(), 2)),
+        _ => None,
+    }
+}
+
+// an "_R1"/"_R2" token, the last one that ends the name or is followed by '_' or '.'
+pub fn mate_token(basename: &str) -> Option<(String, String, u8)> {
+    let (pos, token) = ["_R1", "_R2", ".R1", ".R2"]
+        .iter()
+        .flat_map(|&token| basename.match_indices(token))
+        .filter(|&(pos, token)| {
+            let rest = &basename[pos + token.len()..];
+            rest.is_empty() || rest.starts_with('_') || rest.starts_with('.')
+        })
+        .max_by_key(|&(pos, _)| pos)?;
+    let (sample, rest) = (&basename[..pos], &basename[pos + token.len()..]);
+    let mate = if token.ends_with('1') { 1 } else { 2 };
+    Some((format!("{}#{}", sample, rest), sample.to_string(), mate))
+}
+
+// name, number and extension of a file numbered right before its extension, as in
+// "sample_1.fastq" but also in chunked runs like "FAL123_pass_barcode05_abc_12.fastq.gz"
+pub fn numbered_file(basename: &str) -> Option<(&str, &str, &str)> {
+    let underscore = basename.rfind('_')?;
+    let rest = &basename[underscore + 1..];
+    let (number, ext) = rest.split_at(rest.find('.')?);
+    (!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())).then_some((&basename[..underscore], number, ext))
+}
+
+// read id without the legacy "/1" "/2" mate suffix, so that mates compare equal
+pub fn read_id_key(id: &str) -> &str {
+    id.strip_suffix("/1").or_else(|| id.strip_suffix("/2")).unwrap_or(id)
+}
+
+// mate number from the read header: legacy "/1" "/2" suffix or Casava 1.8+ "1:N:0:..." comment
+pub fn mate_number(id: &str, desc: Option<&str>) -> Option<u8> {
+    if id.ends_with("/1") {
+        return Some(1);
+    }
+    if id.ends_with("/2") {
+        return Some(2);
+    }
+    // Casava comment is <mate>:<is filtered Y/N>:<control number>:<index>
+    let fields: Vec<&str> = desc?.splitn(4, ':').collect();
+    let casava = fields.len() == 4
+        && matches!(fields[1], "Y" | "N")
+        && !fields[2].is_empty()
+        && fields[2].bytes().all(|b| b.is_ascii_digit());
+    match fields[0] {
+        "1" if casava => Some(1),
+        "2" if casava => Some(2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mate_from_read_tokens() {
+        assert_eq!(mate_from_filename("sample_S1_L001_R2_001.fastq.gz"), Some(("sample_S1_L001#_001.fastq.gz".to_string(), "sample_S1_L001".to_string(), 2)));
+        // the last valid token counts, an earlier one in the sample name is not a mate
+        assert_eq!(mate_from_filename("x_R1a_R2.fq").map(|m| m.2), Some(2));
+        assert_eq!(mate_from_filename("run_R1_tmp_R2x.fq").map(|m| m.2), Some(1));
+        assert_eq!(mate_from_filename("sample_1.fastq").map(|m| m.1), Some("sample".to_string()));
+    }
+
+    #[test]
+    fn bare_numbers_only_before_the_extension() {
+        assert_eq!(mate_from_filename("sample_1_trimmed.fastq"), None);
+        assert_eq!(mate_from_filename("sample_3.fastq"), None);
+        // ONT chunks look like mates by name, groups::mate_keys checks for siblings
+        let chunk = "FAL123_pass_barcode05_abc_1.fastq.gz";
+        assert_eq!(numbered_file(chunk), Some(("FAL123_pass_barcode05_abc", "1", ".fastq.gz")));
+        assert_eq!(mate_token(chunk), None);
+        assert_eq!(numbered_file("FAL123_pass_barcode05_abc_12.fastq.gz").map(|n| n.1), Some("12"));
+    }
+
+    #[test]
+    fn mate_number_from_casava_comment() {
+        let id = "A00123:8:H5KJ:1:1101:1000:2000";
+        assert_eq!(mate_number(id, Some("1:N:0:ACGTACGT+TTGGCCAA")), Some(1));
+        assert_eq!(mate_number(id, Some("2:Y:0:ACGTACGT")), Some(2));
+        assert_eq!(mate_number("read7/2", None), Some(2));
+    }
+
+    #[test]
+    fn mate_number_ignores_other_comments() {
+        let id = "SRR000001.1";
+        assert_eq!(mate_number(id, Some("1 length=150")), None);
+        assert_eq!(mate_number(id, Some("2:some:free:text")), None);
+        assert_eq!(mate_number(id, Some("1")), None);
+        assert_eq!(mate_number(id, None), None);
+    }
+}
@@ -0,0 +1,147 @@
+// Index (barcode) tabulation for demultiplexing QC, e.g. Undetermined_S0_*.fastq.gz
+
+use std::collections::BTreeMap;
+
+use crate::modules::revcomp;
+
+#[derive(Clone, PartialEq)]
+pub struct SampleIndex {
+    pub name: String,
+    pub i7: String,
+    pub i5: Option<String>,
+}
+
+// Accepts either an Illumina sample sheet (the [Data] section with index/index2 columns)
+// or plain "name,i7[,i5]" lines
+pub fn parse_samplesheet(text: &str) -> Vec<SampleIndex> {
+    let mut samples = Vec::new();
+    let mut in_section = !text.contains("[Data]");
+    let mut columns: Option<(usize, usize, Option<usize>)> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_section = line.starts_with("[Data]");
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let fields: Vec<&str> = line.split([',', '\t', ';']).map(|f| f.trim()).collect();
+
+        // header line, find the columns we need
+        if fields.iter().any(|f| f.eq_ignore_ascii_case("index")) {
+            let find = |names: &[&str]| fields.iter().position(|f| names.iter().any(|n| f.eq_ignore_ascii_case(n)));
+            let name_col = find(&["Sample_ID", "Sample_Name", "Sample", "Name"]).unwrap_or(0);
+            let i7_col = find(&["index"]).unwrap_or(1);
+            columns = Some((name_col, i7_col, find(&["index2"])));
+            continue;
+        }
+
+        let (name_col, i7_col, i5_col) = columns.unwrap_or((0, 1, Some(2)));
+        let i7 = match fields.get(i7_col) {
+            Some(s) if is_dna(s) => s.to_ascii_uppercase(),
+            _ => continue,
+        };
+        let i5 = i5_col
+            .and_then(|c| fields.get(c))
+            .filter(|s| is_dna(s))
+            .map(|s| s.to_ascii_uppercase());
+        samples.push(SampleIndex {
+            name: fields.get(name_col).unwrap_or(&"").to_string(),
+            i7,
+            i5,
+        });
+    }
+    samples
+}
+
+fn is_dna(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| matches!(b, b'A' | b'C' | b'G' | b'T' | b'N' | b'a' | b'c' | b'g' | b't' | b'n'))
+}
+
+// observed index may be longer than the expected one (e.g. 10 bp read, 8 bp index)
+fn mismatches(observed: &str, expected: &str) -> usize {
+    if observed.len() < expected.len() {
+        return usize::MAX;
+    }
+    observed
+        .bytes()
+        .zip(expected.bytes())
+        .filter(|(a, b)| a != b)
+        .count()
+}
+
+fn matches_one(observed: &str, expected: &str) -> bool {
+    mismatches(observed, expected) <= 1
+}
+
+fn split_barcode(barcode: &str) -> (&str, Option<&str>) {
+    match barcode.split_once('+') {
+        Some((i7, i5)) => (i7, Some(i5)),
+        None => (barcode, None),
+    }
+}
+
+// sample whose index pair matches the barcode with at most 1 mismatch per index
+pub fn assign<'a>(barcode: &str, samples: &'a [SampleIndex]) -> Option<&'a SampleIndex> {
+    let (i7, i5) = split_barcode(barcode);
+    samples.iter().find(|s| {
+        matches_one(i7, &s.i7)
+            && match (i5, &s.i5) {
+                (Some(obs), Some(exp)) => matches_one(obs, exp),
+                _ => true,
+            }
+    })
+}
+
+// explain an unassigned barcode in terms of the sample sheet
+pub fn hint(barcode: &str, samples: &[SampleIndex]) -> String {
+    let (i7, i5) = split_barcode(barcode);
+    let find = |f: &dyn Fn(&SampleIndex) -> bool| samples.iter().find(|s| f(s)).map(|s| s.name.clone());
+
+    if let Some(i5) = i5 {
+        if let Some(name) = find(&|s| s.i5.as_ref().is_some_and(|exp| matches_one(i7, exp) && matches_one(i5, &s.i7))) {
+            return format!("i7/i5 swapped ({})", name);
+        }
+        if let Some(name) = find(&|s| matches_one(i7, &s.i7) && s.i5.as_ref().is_some_and(|exp| matches_one(i5, &revcomp_str(exp)))) {
+            return format!("i5 reverse complement ({})", name);
+        }
+        if let Some(name) = find(&|s| matches_one(i7, &revcomp_str(&s.i7)) && s.i5.as_ref().is_some_and(|exp| matches_one(i5, exp))) {
+            return format!("i7 reverse complement ({})", name);
+        }
+        let i7_sample = find(&|s| matches_one(i7, &s.i7));
+        let i5_sample = find(&|s| s.i5.as_ref().is_some_and(|exp| matches_one(i5, exp)));
+        match (i7_sample, i5_sample) {
+            (Some(a), Some(b)) => return format!("index hopping: i7 {} + i5 {}", a, b),
+            (Some(a), None) => return format!("i7 {}, unknown i5", a),
+            (None, Some(b)) => return format!("unknown i7, i5 {}", b),
+            (None, None) => {}
+        }
+    } else if let Some(name) = find(&|s| matches_one(i7, &revcomp_str(&s.i7))) {
+        return format!("i7 reverse complement ({})", name);
+    }
+
+    if i7.bytes().filter(|&b| b == b'N').count() > 1 {
+        return "low quality index (N)".to_string();
+    }
+    if i7.bytes().all(|b| b == b'G') {
+        return "poly-G (no index read)".to_string();
+    }
+    String::new()
+}
+
+fn revcomp_str(seq: &str) -> String {
+    String::from_utf8(revcomp(seq.as_bytes())).unwrap_or_default()
+}
+
+// barcodes sorted by read count, most abundant first
+pub fn top_barcodes(counts: &BTreeMap<String, u64>, n: usize) -> Vec<(&String, u64)> {
+    let mut sorted: Vec<(&String, u64)> = counts.iter().map(|(k, &v)| (k, v)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    sorted.truncate(n);
+    sorted
+}
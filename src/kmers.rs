@@ -0,0 +1,71 @@
+// Canonical k-mer hashing, shared by contamination screening and MinHash sketches
+
+fn encode(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+// invertible 64-bit mix, so that hashes of 2-bit encoded k-mers are spread uniformly
+pub fn hash64(key: u64) -> u64 {
+    let mut key = key;
+    key = (!key).wrapping_add(key << 21);
+    key ^= key >> 24;
+    key = key.wrapping_add(key << 3).wrapping_add(key << 8);
+    key ^= key >> 14;
+    key = key.wrapping_add(key << 2).wrapping_add(key << 4);
+    key ^= key >> 28;
+    key.wrapping_add(key << 31)
+}
+
+// hash of every canonical k-mer (k <= 32) with its start position, k-mers with N are skipped
+pub fn canonical_hashes(seq: &[u8], k: usize, mut f: impl FnMut(usize, u64)) {
+    let mask = if k >= 32 { u64::MAX } else { (1u64 << (2 * k)) - 1 };
+    let shift = 2 * (k as u64 - 1);
+    let (mut fwd, mut rev, mut valid) = (0u64, 0u64, 0usize);
+
+    for (i, &base) in seq.iter().enumerate() {
+        match encode(base) {
+            Some(code) => {
+                fwd = ((fwd << 2) | code) & mask;
+                rev = (rev >> 2) | ((3 - code) << shift);
+                valid += 1;
+                if valid >= k {
+                    f(i + 1 - k, hash64(fwd.min(rev)));
+                }
+            }
+            None => valid = 0,
+        }
+    }
+}
+
+// (w, k)-minimizers: the smallest hash in every window of w consecutive k-mers
+pub fn minimizers(seq: &[u8], k: usize, w: usize) -> Vec<u64> {
+    let mut result = Vec::new();
+    let mut segment: Vec<u64> = Vec::new();
+    let mut last_pos = usize::MAX;
+
+    let flush = |segment: &mut Vec<u64>, result: &mut Vec<u64>| {
+        for window in segment.windows(w.min(segment.len()).max(1)) {
+            let min = *window.iter().min().unwrap();
+            if result.last() != Some(&min) {
+                result.push(min);
+            }
+        }
+        segment.clear();
+    };
+
+    canonical_hashes(seq, k, |pos, hash| {
+        if last_pos != usize::MAX && pos != last_pos + 1 {
+            flush(&mut segment, &mut result);
+        }
+        segment.push(hash);
+        last_pos = pos;
+    });
+    flush(&mut segment, &mut result);
+    result
+}
@@ -4,7 +4,6 @@ use std::io::Cursor;
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
 use std::path::Path;
-use std::sync::Arc;
 //use std::io::Cursor;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
@@ -14,13 +13,13 @@ use arboard::Clipboard;
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::FileDialog;
 
-use bio::io::fastq;
 use flate2::read::MultiGzDecoder;
 
 #[cfg(not(target_arch = "wasm32"))]
 use dioxus::desktop::{Config, LogicalSize, WindowBuilder};
 //use dioxus::prelude::dioxus_elements::FileEngine;
 use dioxus::prelude::*;
+use dioxus::html::HasFileData;
 #[cfg(target_arch = "wasm32")]
 use dioxus::prelude::document::eval;
 
@@ -36,6 +35,28 @@ use wasm_bindgen_futures as _; // Or just remove the line if not needed
 
 mod modules;
 mod components;
+mod headers;
+mod indexes;
+mod groups;
+mod sources;
+mod stats;
+mod filter;
+mod exports;
+mod subsample;
+mod reads;
+mod search;
+mod kmers;
+mod screen;
+mod sketch;
+mod bins;
+mod charts;
+mod density;
+mod yields;
+mod positions;
+mod detail;
+mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 
 async fn my_yield() {
     #[cfg(not(target_arch = "wasm32"))]
@@ -48,6 +69,27 @@ async fn my_yield() {
     }
 }
 
+// insert sizes of newly complete pairs, computed off the UI task
+async fn pair_inserts(mut files_uploaded: Signal<Vec<UploadedFile>>) {
+    let pairs = groups::take_pairs(&mut files_uploaded.write());
+    for (name, r1, r2) in pairs {
+        #[cfg(not(target_arch = "wasm32"))]
+        let inserts = task::spawn_blocking(move || groups::insert_sizes(r1.iter().zip(r2.iter()))).await.unwrap_or_default();
+        #[cfg(target_arch = "wasm32")]
+        let inserts = {
+            let mut inserts = Vec::new();
+            for (r1, r2) in r1.chunks(500).zip(r2.chunks(500)) {
+                inserts.extend(groups::insert_sizes(r1.iter().zip(r2.iter())));
+                my_yield().await; // Yield every 500 pairs to keep UI responsive
+            }
+            inserts
+        };
+        if let Some(f) = files_uploaded.write().iter_mut().find(|f| f.name == name) {
+            f.inserts = inserts;
+        }
+    }
+}
+
 async fn my_sleep(ms: u64) {
     #[cfg(not(target_arch = "wasm32"))]
     tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
@@ -74,6 +116,9 @@ fn main() {
 
     #[cfg(not(target_arch = "wasm32"))]
     {
+        if let Some(code) = cli::run(&std::env::args().collect::<Vec<_>>()) {
+            std::process::exit(code);
+        }
         let config = Config::new()
         .with_window(
             WindowBuilder::new()
@@ -86,7 +131,7 @@ fn main() {
             .launch(app)
     }
 }
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 struct UploadedFile {
     name: String,
     basename: String,
@@ -94,12 +139,26 @@ struct UploadedFile {
     bases: u64,
     nx: u64,
     l_vector: Vec<i64>,
-    gc: String,
-    q20: String,
-    q30: String,
+    gc: metrics::Metric,
+    q20: metrics::Metric,
+    q30: metrics::Metric,
     m_qscore: u8, // median q score
     q_vector: Vec<u8>, // Add this field to store the quality scores
-    q_hash: BTreeMap<u8, i64> 
+    q_hash: BTreeMap<u8, i64>,
+    index_counts: BTreeMap<String, u64>, // Illumina index sequences from the read headers
+    platform: String,
+    hifi: headers::HifiStats,
+    gc_bases: u64,
+    id_hashes: Vec<u64>, // hashed read ids, only kept for R1/R2 files to check pairing
+    interleaved: bool, // reads alternate R1/R2, so even records are R1 and odd ones R2
+    mate_gc_bases: [u64; 2],
+    mate_q_hash: [BTreeMap<u8, i64>; 2],
+    seq_sample: Vec<Vec<u8>>, // first reads of an R1/R2 file until its mate is loaded, for insert sizes
+    inserts: Vec<i64>, // insert sizes of interleaved files, and of R1/R2 pairs on the R1 file
+    screen: screen::ScreenCounts, // contamination screen of a read sample
+    density: density::Density, // read length x mean quality
+    yields: yields::YieldMatrix, // bases above length and quality cutoffs
+    positions: positions::Positions, // quality and composition along the reads
 }
 
 async fn decode_reader(bytes: Vec<u8>, filename: &String) -> std::io::Result<Box<dyn std::io::Read + Send>> {
@@ -113,32 +172,52 @@ async fn decode_reader(bytes: Vec<u8>, filename: &String) -> std::io::Result<Box
     }
 }
 
+fn compare_files(a: &UploadedFile, b: &UploadedFile, column: &str) -> std::cmp::Ordering {
+    match column {
+        "name" => natord::compare(&a.name, &b.name),
+        "platform" => a.platform.cmp(&b.platform),
+        _ => metrics::column(a, column).order(&metrics::column(b, column)),
+    }
+}
+
 fn maketable(
     entries: Signal<Vec<UploadedFile>>,
+    groups: Memo<Vec<groups::Group>>,
     name_type: String,
     numbers_type: String,
-    binsize: Signal<usize>,
-    spark_type: Signal<String>,
-    treads: Signal<u64>,
-    tbases: Signal<u64>,
+    sparklines: Memo<Sparklines>,
+    total: Memo<UploadedFile>, // pooled files of the total row
     sort_by: Signal<(String, bool)>, // Track column and sort direction
+    expanded: Signal<std::collections::BTreeSet<String>>, // groups showing their member rows
+    screen_refs: &[String], // references of the last contamination screen
+    selected: Signal<std::collections::BTreeSet<String>>,
+    detail: Signal<Option<String>>, // row shown on the detail page
     ) -> Element {
-    let mut sorted_entries = entries.read().clone();
+    let mut sorted_groups = groups.read().clone();
 
     // Sort entries based on the current column and direction
+    let show_hifi = entries.read().iter().any(|f| f.platform == "PacBio");
+    let show_inserts = sorted_groups.iter().any(|g| !g.inserts.is_empty());
+    let opts = RowOptions {
+        numbers_type: numbers_type.clone(),
+        sparklines: sparklines(),
+        show_hifi,
+        screen_columns: screen_refs.len(),
+        selected,
+        detail,
+    };
+    // the pooled row is scaled on its own, a shared y-axis would clip it
+    let total_opts = RowOptions {
+        numbers_type: numbers_type.clone(),
+        sparklines: Sparklines { y_scale: YScale::default(), ..sparklines() },
+        show_hifi,
+        screen_columns: screen_refs.len(),
+        selected,
+        detail,
+    };
     let (column, ascending) = sort_by.read().clone();
-    sorted_entries.sort_by(|a, b| {
-        let order = match column.as_str() {
-            "name" => natord::compare(&a.name, &b.name),
-            "reads" => a.reads.cmp(&b.reads),
-            "bases" => a.bases.cmp(&b.bases),
-            "nx" => a.nx.cmp(&b.nx),
-            "gc" => a.gc.cmp(&b.gc),
-            "q20" => a.q20.cmp(&b.q20),
-            "q30" => a.q30.cmp(&b.q30),
-            "m_qscore" => a.m_qscore.cmp(&b.m_qscore),
-            _ => std::cmp::Ordering::Equal,
-        };
+    sorted_groups.sort_by(|a, b| {
+        let order = compare_files(&a.pooled, &b.pooled, &column);
         if ascending {
             order
         } else {
@@ -147,87 +226,185 @@ fn maketable(
     });
 
     rsx! {
-        for f in sorted_entries.iter() {
-            tr {
-                if name_type == "fullpath" {
-                    td { class: "filename-cell", "{f.name}" }
+        for g in sorted_groups.iter() {
+            if g.members.len() > 1 {
+                {table_row(&g.pooled, "group-row", row_name(&g.pooled, &opts, group_name_cell(g, &name_type, expanded)), &opts, show_inserts.then_some(&g.inserts))}
+                if expanded.read().contains(&g.pooled.name) {
+                    for f in g.members.iter() {
+                        {table_row(f, "member-row", row_name(f, &opts, name_cell(f, &name_type, "")), &opts, show_inserts.then_some(&Vec::new()))}
+                    }
+                }
+            } else {
+                {table_row(&g.pooled, "", row_name(&g.pooled, &opts, name_cell(&g.pooled, &name_type, &g.note)), &opts, show_inserts.then_some(&g.inserts))}
+            }
+        }
+        {table_row(&total.read(), "total-row", rsx! { "{total.read().basename}" }, &total_opts, show_inserts.then_some(&Vec::new()))}
+    }
+}
+
+fn name_cell(f: &UploadedFile, name_type: &str, note: &str) -> Element {
+    rsx! {
+        if name_type == "fullpath" { "{f.name}" } else { "{f.basename}" }
+        if !note.is_empty() {
+            div { class: "row-note", "{note}" }
+        }
+    }
+}
+
+// pooled row of a group, clicking the arrow shows or hides the member files
+fn group_name_cell(g: &groups::Group, name_type: &str, mut expanded: Signal<std::collections::BTreeSet<String>>) -> Element {
+    let key = g.pooled.name.clone();
+    let is_open = expanded.read().contains(&key);
+    let members = g.members.len();
+    let note = if g.note.is_empty() { format!("{} files", members) } else { format!("{}, {} files", g.note, members) };
+    rsx! {
+        span {
+            class: "group-toggle",
+            onclick: move |ev| {
+                ev.stop_propagation(); // the name around it opens the detail page
+                if is_open {
+                    expanded.write().remove(&key);
                 } else {
-                    td { class: "filename-cell", "{f.basename}" }
+                    expanded.write().insert(key.clone());
                 }
-                if numbers_type == "comma" {
-                    td {"{HumanCount(f.reads)}"}
-                    td {"{HumanCount(f.bases)}"}
-                    td {"{HumanCount(f.nx)}"}
-                } else if numbers_type == "human" {
-                    td {"{f.reads.human_count_bare()}"}
-                    td {"{f.bases.human_count_bare()}"}
-                    td {"{f.nx.human_count_bare()}"}
+            },
+            if is_open { "▾ " } else { "▸ " }
+        }
+        {name_cell(&g.pooled, name_type, &note)}
+    }
+}
+
+// display settings shared by all rows of the table
+struct RowOptions {
+    numbers_type: String,
+    sparklines: Sparklines,
+    show_hifi: bool,
+    screen_columns: usize,
+    selected: Signal<std::collections::BTreeSet<String>>, // rows ticked for comparison
+    detail: Signal<Option<String>>,
+}
+
+// tick box for the comparison and the name, which opens the detail page
+fn row_name(f: &UploadedFile, opts: &RowOptions, name_cell: Element) -> Element {
+    let (mut selected, mut detail) = (opts.selected, opts.detail);
+    let key = f.name.clone();
+    let detail_key = f.name.clone();
+    let ticked = selected.read().contains(&key);
+    rsx! {
+        input {
+            r#type: "checkbox",
+            class: "row-select",
+            checked: ticked,
+            onchange: move |_| {
+                if ticked {
+                    selected.write().remove(&key);
                 } else {
-                    td {"{f.reads}"}
-                    td {"{f.bases}"}
-                    td {"{f.nx}"}
+                    selected.write().insert(key.clone());
+                }
+            }
+        }
+        span {
+            class: "file-link",
+            title: "Show all metrics and plots",
+            onclick: move |_| detail.set(Some(detail_key.clone())),
+            {name_cell}
+        }
+    }
+}
+
+fn table_row(
+    f: &UploadedFile,
+    row_class: &str,
+    name_cell: Element,
+    opts: &RowOptions,
+    inserts: Option<&Vec<i64>>, // None hides the insert size columns
+    ) -> Element {
+    let RowOptions { numbers_type, sparklines, show_hifi, screen_columns, .. } = opts;
+    let Sparklines { length_bins, quality_bins, y_scale, plot_type: spark_type } = sparklines;
+    let show_hifi = *show_hifi;
+    rsx! {
+        tr {
+            class: "{row_class}",
+            td {
+                class: "filename-cell",
+                {name_cell}
+            }
+            td { class: "platform-cell", "{f.platform}" }
+            for key in ["reads", "bases", "nx"] {
+                td {"{metrics::column(f, key).display(numbers_type)}"}
+            }
+            td {
+                class: "histogram-cell",
+                dangerous_inner_html: "{generate_l_histogram(&f.l_vector, length_bins, spark_type.clone(), y_scale)}" // Render the histogram as HTML
+            }
+            for key in ["gc", "q30", "m_qscore"] {
+                td {"{metrics::column(f, key).display(numbers_type)}"}
+            }
+            if spark_type != "reads" {
+                td {
+                    class: "histogram-cell",
+                    dangerous_inner_html: "{generate_qbases_histogram(&f.q_hash, spark_type.clone(), quality_bins, y_scale)}{q_markers(f, quality_bins)}" // Render the histogram as HTML
                 }
+            } else {
                 td {
                     class: "histogram-cell",
-                    dangerous_inner_html: "{generate_l_histogram(&f.l_vector, binsize(), spark_type())}" // Render the histogram as HTML
+                    dangerous_inner_html: "{generate_q_histogram(&f.q_vector, quality_bins, y_scale)}{q_markers(f, quality_bins)}" // Render the histogram as HTML
                 }
-                td {"{f.gc}"}
-                //td {"{f.q20}"}
-                td {"{f.q30}"}
-                td {"{f.m_qscore}"}
-                if spark_type() != "reads" {
-                    td {
-                        class: "histogram-cell",
-                        dangerous_inner_html: "{generate_qbases_histogram(&f.q_hash, spark_type())}" // Render the histogram as HTML
-                    }
+            }
+            if show_hifi {
+                for key in HIFI_COLUMNS {
+                    td {"{metrics::column(f, key).display(numbers_type)}"}
+                }
+            }
+            if let Some(inserts) = inserts {
+                if inserts.is_empty() {
+                    td {  }
+                    td {  }
                 } else {
+                    td {"{modules::median_i64(inserts)}"}
                     td {
                         class: "histogram-cell",
-                        dangerous_inner_html: "{generate_q_histogram(&f.q_vector)}" // Render the histogram as HTML
+                        dangerous_inner_html: "{generate_l_histogram(inserts, &bins::LengthBins::fixed(groups::insert_binsize(inserts), bins::DEFAULT_COUNT), \"reads\".to_string(), &YScale::default())}"
                     }
                 }
             }
-        }
-        tr {
-            class: "total-row",
-            td {"Total ({entries.len()} files)"}
-            if numbers_type == "comma" {
-                td {"{HumanCount(treads())}"}
-                td {"{HumanCount(tbases())}"}
-            } else if numbers_type == "human" {
-                td {"{treads().human_count_bare()}"}
-                td {"{tbases().human_count_bare()}"}
-            } else {
-                td {"{treads()}"}
-                td {"{tbases()}"}
+            for i in 0..*screen_columns {
+                td {"{metrics::Metric::from_percent(f.screen.percent(i)).display(numbers_type)}"}
             }
-            td {  }
-            td {  }
-            td {  }
-            td {  }
-            td {  }
-            td {  }
         }
     }
 }
 
-fn copy_to_clipboard(f_uploaded: Signal<Vec<UploadedFile>>) {
+// numeric columns of the clipboard table after file and platform, and the PacBio columns of all exports
+const CSV_COLUMNS: [&str; 7] = ["reads", "bases", "nx", "gc", "q20", "q30", "m_qscore"];
+const HIFI_COLUMNS: [&str; 3] = ["passes", "rq_q20", "rq_q30"];
+
+fn copy_to_clipboard(f_uploaded: Signal<Vec<UploadedFile>>, total: &UploadedFile, screen_refs: &[String]) {
     let mut csv_data = String::new();
-    csv_data.push_str("File,Reads,Bases,N50,GC%,Q20%,Q30%,Median_Qscore\n");
-
-    for file in f_uploaded.read().iter() {
-        csv_data.push_str(&format!(
-            "{},{},{},{},{},{},{},{}\n",
-            //file.name,
-            file.basename,
-            file.reads,
-            file.bases,
-            file.nx,
-            file.gc,
-            file.q20,
-            file.q30,
-            file.m_qscore
-        ));
+    let show_hifi = f_uploaded.read().iter().any(|f| f.platform == "PacBio");
+    csv_data.push_str("File,Platform,Reads,Bases,N50,GC%,Q20%,Q30%,Median_Qscore");
+    if show_hifi {
+        csv_data.push_str(",Mean_passes,rq_Q20%,rq_Q30%");
+    }
+    for name in screen_refs {
+        csv_data.push_str(&format!(",{}%", name));
+    }
+    csv_data.push('\n');
+
+    for file in f_uploaded.read().iter().chain([total]) {
+        csv_data.push_str(&format!("{},{}", file.basename, file.platform));
+        for key in CSV_COLUMNS {
+            csv_data.push_str(&format!(",{}", metrics::column(file, key).export()));
+        }
+        if show_hifi {
+            for key in HIFI_COLUMNS {
+                csv_data.push_str(&format!(",{}", metrics::column(file, key).export()));
+            }
+        }
+        for i in 0..screen_refs.len() {
+            csv_data.push_str(&format!(",{}", metrics::Metric::from_percent(file.screen.percent(i)).export()));
+        }
+        csv_data.push('\n');
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -247,7 +424,16 @@ fn copy_to_clipboard(f_uploaded: Signal<Vec<UploadedFile>>) {
     }
 }
 
-fn save_html(f_uploaded: Signal<Vec<UploadedFile>>, numbers_type: String, name_type: String, binsize: usize, plot_type: String) {
+#[allow(clippy::too_many_arguments)]
+fn save_html(
+    f_uploaded: Signal<Vec<UploadedFile>>,
+    total: &UploadedFile,
+    numbers_type: String,
+    name_type: String,
+    sparklines: &Sparklines,
+    screen_refs: &[String],
+) {
+    let Sparklines { length_bins, quality_bins, y_scale, plot_type } = sparklines;
     let html_data = {
         let mut html_data = String::new();
 
@@ -263,90 +449,142 @@ fn save_html(f_uploaded: Signal<Vec<UploadedFile>>, numbers_type: String, name_t
         html_data.push_str("<p>Generated on: ");
         html_data.push_str(&format!("{}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S")));
         html_data.push_str("</p>\n");
+        html_data.push_str(&format!(
+            "<p>Length histograms: {}. Quality histograms: {}, dashed line at the mean and solid line at the median read Q. Histogram y-axis: {}.</p>\n",
+            length_bins.describe(),
+            quality_bins.describe(),
+            y_scale.describe()
+        ));
         
+        let show_hifi = f_uploaded.read().iter().any(|f| f.platform == "PacBio");
+
         // Add table structure
         html_data.push_str("<table id='resultstable'>\n<thead>\n<tr>\n");
         if plot_type == "bases" {
-            html_data.push_str("<th>File</th><th>Reads</th><th>Bases</th><th>N50</th><th class='histogram-header'>Bases Length Histogram</th><th>GC%</th><th>Q30%</th><th>Median Qscore</th><th class='histogram-header'>Bases Qscore Histogram</th>\n");
+            html_data.push_str("<th>File</th><th>Platform</th><th>Reads</th><th>Bases</th><th>N50</th><th class='histogram-header'>Bases Length Histogram</th><th>GC%</th><th>Q30%</th><th>Median Qscore</th><th class='histogram-header'>Bases Qscore Histogram</th>\n");
         } else {
-            html_data.push_str("<th>File</th><th>Reads</th><th>Bases</th><th>N50</th><th class='histogram-header'>Reads Length Histogram</th><th>GC%</th><th>Q30%</th><th>Median Qscore</th><th class='histogram-header'>Reads Qscore Histogram</th>\n");
+            html_data.push_str("<th>File</th><th>Platform</th><th>Reads</th><th>Bases</th><th>N50</th><th class='histogram-header'>Reads Length Histogram</th><th>GC%</th><th>Q30%</th><th>Median Qscore</th><th class='histogram-header'>Reads Qscore Histogram</th>\n");
+        }
+        if show_hifi {
+            html_data.push_str("<th>Mean passes</th><th>rq Q20%</th><th>rq Q30%</th>\n");
+        }
+        for name in screen_refs {
+            html_data.push_str(&format!("<th>{} %</th>", charts::escape(name)));
         }
         //html_data.push_str("<th>File</th><th>Reads</th><th>Bases</th><th>N50</th><th class='histogram-header'>Length Histogram</th><th>GC%</th><th>Q30%</th><th>Median Qscore</th><th class='histogram-header'>Qscore Histogram</th>\n");
         
         html_data.push_str("</tr>\n</thead>\n<tbody>\n");
 
-        // Add table rows
-        for file in f_uploaded.read().iter() {
-            html_data.push_str("<tr>\n");
+        // Add table rows, then the pooled total scaled on its own
+        let rows = f_uploaded.read();
+        let own_scale = YScale::default();
+        for (i, file) in rows.iter().chain([total]).enumerate() {
+            let is_total = i == rows.len();
+            let y_scale = if is_total { &own_scale } else { y_scale };
+            html_data.push_str(if is_total { "<tr class='total-row'>\n" } else { "<tr>\n" });
             if name_type == "fullpath" {
                 html_data.push_str(&format!("<td>{}</td>\n", file.name));
             } else {
                 html_data.push_str(&format!("<td>{}</td>\n", file.basename));
             }
-
-            if numbers_type == "comma" {
-                html_data.push_str(&format!("<td>{}</td>\n", HumanCount(file.reads)));
-                html_data.push_str(&format!("<td>{}</td>\n", HumanCount(file.bases)));
-                html_data.push_str(&format!("<td>{}</td>\n", HumanCount(file.nx)));
-            } else if numbers_type == "human" {
-                html_data.push_str(&format!("<td>{}</td>\n", file.reads.human_count_bare()));
-                html_data.push_str(&format!("<td>{}</td>\n", file.bases.human_count_bare()));
-                html_data.push_str(&format!("<td>{}</td>\n", file.nx.human_count_bare()));
-            } else {
-                html_data.push_str(&format!("<td>{}</td>\n", file.reads));
-                html_data.push_str(&format!("<td>{}</td>\n", file.bases));
-                html_data.push_str(&format!("<td>{}</td>\n", file.nx));
+            html_data.push_str(&format!("<td>{}</td>\n", file.platform));
+            for key in ["reads", "bases", "nx"] {
+                html_data.push_str(&format!("<td>{}</td>\n", metrics::column(file, key).display(&numbers_type)));
             }
 
             // Embed the length histogram as raw HTML
             html_data.push_str(&format!(
                 "<td class='histogram-cell'>{}</td>\n",
-                generate_l_histogram(&file.l_vector, binsize, plot_type.clone())
+                generate_l_histogram(&file.l_vector, length_bins, plot_type.clone(), y_scale)
             ));
 
-            html_data.push_str(&format!("<td>{}</td>\n", file.gc));
-            html_data.push_str(&format!("<td>{}</td>\n", file.q30));
-            html_data.push_str(&format!("<td>{}</td>\n", file.m_qscore));
+            for key in ["gc", "q30", "m_qscore"] {
+                html_data.push_str(&format!("<td>{}</td>\n", metrics::column(file, key).display(&numbers_type)));
+            }
 
             // Embed the Qscore histogram as raw HTML
             html_data.push_str(&format!(
-                "<td class='histogram-cell'>{}</td>\n",
+                "<td class='histogram-cell'>{}{}</td>\n",
                 if plot_type != "reads" {
-                    generate_qbases_histogram(&file.q_hash, plot_type.clone())
+                    generate_qbases_histogram(&file.q_hash, plot_type.clone(), quality_bins, y_scale)
                 } else {
-                    generate_q_histogram(&file.q_vector)
-            }
+                    generate_q_histogram(&file.q_vector, quality_bins, y_scale)
+            },
+                q_markers(file, quality_bins)
             ));
 
+            if show_hifi {
+                for key in HIFI_COLUMNS {
+                    html_data.push_str(&format!("<td>{}</td>\n", metrics::column(file, key).display(&numbers_type)));
+                }
+            }
+            for i in 0..screen_refs.len() {
+                html_data.push_str(&format!("<td>{}</td>\n", metrics::Metric::from_percent(file.screen.percent(i)).display(&numbers_type)));
+            }
+
             html_data.push_str("</tr>\n");
         }
 
         html_data.push_str("</tbody>\n</table>\n");
+
+        // full-size plots, hover a bar or point for its values
+        html_data.push_str("<h3>Plots</h3>\n");
+        for file in f_uploaded.read().iter() {
+            let name = if name_type == "fullpath" { &file.name } else { &file.basename };
+            html_data.push_str(&format!("<h4>{}</h4>\n<div class='chart-row'>\n", charts::escape(name)));
+            let lengths = [charts::Input { name, data: &file.l_vector }];
+            for chart in [
+                charts::length_chart(&lengths, length_bins, plot_type, None, false),
+                charts::yield_chart(&lengths, length_bins.log, None, false),
+                charts::quality_chart(&[charts::Input { name, data: &file.q_vector }], false),
+            ] {
+                html_data.push_str(&chart.to_svg());
+                html_data.push('\n');
+            }
+            html_data.push_str(&charts::density_svg(&file.density));
+            html_data.push_str("</div>\n");
+            html_data.push_str(&file.yields.to_html());
+        }
+        if f_uploaded.read().len() > 1 {
+            let files = f_uploaded.read();
+            let mut pooled = yields::YieldMatrix::default();
+            for file in files.iter() {
+                pooled.merge(&file.yields);
+            }
+            html_data.push_str(&format!("<h4>All {} files</h4>\n", files.len()));
+            html_data.push_str(&pooled.to_html());
+        }
         html_data.push_str("</body>\n</html>");
 
         html_data
     };
 
+    save_file("Save HTML File", "fasterx_results.html", html_data.into_bytes(), "text/html");
+}
+
+// native save dialog on desktop, browser download on web
+#[allow(unused_variables)] // the title is used on desktop only, the mime type on web only
+fn save_file(title: &'static str, file_name: &str, data: Vec<u8>, mime: &'static str) {
     // Use conditional logic based on target
     #[cfg(not(target_arch = "wasm32"))]
     {
         // Use `spawn_blocking` to avoid blocking the main thread
+        let file_name = file_name.to_string();
         task::spawn_blocking(move || {
             let path = std::env::current_dir().unwrap_or_default();
 
             if let Some(mypath) = FileDialog::new()
-                .set_title("Save HTML File")
+                .set_title(title)
                 .set_directory(&path)
-                .set_file_name("fasterx_results.html")
+                .set_file_name(&file_name)
                 .save_file()
             {
-                // Save the HTML to the selected file
                 if let Ok(mut file) = File::create(mypath) {
-                    if let Err(e) = file.write_all(html_data.as_bytes()) {
-                        eprintln!("Failed to write HTML file: {}", e);
+                    if let Err(e) = file.write_all(&data) {
+                        eprintln!("Failed to write {}: {}", file_name, e);
                     }
                 } else {
-                    eprintln!("Failed to create HTML file");
+                    eprintln!("Failed to create {}", file_name);
                 }
             } else {
                 eprintln!("Save operation canceled");
@@ -355,32 +593,7 @@ fn save_html(f_uploaded: Signal<Vec<UploadedFile>>, numbers_type: String, name_t
     }
 
     #[cfg(target_arch = "wasm32")]
-    {
-        use wasm_bindgen::JsCast;
-        let window = web_sys::window().expect("window not found");
-        let document = window.document().expect("document not found");
-        
-        // Create blob
-        let parts = js_sys::Array::new();
-        parts.push(&wasm_bindgen::JsValue::from_str(&html_data));
-        let blob_props = web_sys::BlobPropertyBag::new();
-        blob_props.set_type("text/html");
-        let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &blob_props).expect("failed to create blob");
-        
-        // Create URL
-        let url = web_sys::Url::create_object_url_with_blob(&blob).expect("failed to create URL");
-        
-        // Create anchor and click it
-        let a = document.create_element("a").expect("failed to create anchor").dyn_into::<web_sys::HtmlAnchorElement>().expect("failed to cast anchor");
-        a.set_href(&url);
-        a.set_download("fasterx_results.html");
-        document.body().expect("body not found").append_child(&a).expect("failed to append anchor");
-        a.click();
-        
-        // Cleanup
-        document.body().unwrap().remove_child(&a).expect("failed to remove anchor");
-        web_sys::Url::revoke_object_url(&url).expect("failed to revoke URL");
-    }
+    exports::download(file_name, &data, mime);
 }
 
 fn format_thead(sortby: Signal<(String, bool)>, sortcol: &str) -> String {
@@ -395,18 +608,108 @@ fn format_thead(sortby: Signal<(String, bool)>, sortcol: &str) -> String {
     }
 }
 
-fn generate_q_histogram(q_vector: &[u8]) -> String {
-    let mut bins = [0; 30]; // Create 30 bins for the histogram
-    let max_bin_index = bins.len() - 1; // Index of the last bin
+// bins and scales of the sparklines, shared by the table and the HTML report
+#[derive(Clone, PartialEq)]
+struct Sparklines {
+    length_bins: bins::LengthBins,
+    quality_bins: bins::QualityBins,
+    y_scale: YScale,
+    plot_type: String,
+}
 
-    for &q in q_vector {
-        let bin = (q / 2) as usize; // bin spans 2 qvalues e.g. 8-10
-        if bin < bins.len() {
-            bins[bin] += 1;
-        } else {
-            bins[max_bin_index] += 1; // Increment the last bin for out-of-range values
+// y-axis of the sparklines: each file's own maximum bin, the maximum over all files ("shared"),
+// or the share of the file ("percent"), scaled to the largest share over all files
+#[derive(Clone, Default, PartialEq)]
+struct YScale {
+    mode: String,
+    length: f64,
+    qscore: f64,
+    qbases: f64,
+}
+
+impl YScale {
+    fn for_files<'a>(
+        mode: &str,
+        files: impl Iterator<Item = &'a UploadedFile>,
+        length_bins: &bins::LengthBins,
+        quality_bins: &bins::QualityBins,
+        plot_type: &str,
+    ) -> Self {
+        let mut scale = YScale { mode: mode.to_string(), length: 0.0, qscore: 0.0, qbases: 0.0 };
+        if mode != "shared" && mode != "percent" {
+            return scale;
+        }
+        let fold = |current: f64, own_max: f64, total: f64| {
+            let value = if mode == "shared" { own_max } else { own_max / total };
+            if value.is_finite() { current.max(value) } else { current }
+        };
+        for f in files {
+            let (reads, bases) = length_bins.count(&f.l_vector);
+            let total_bases = bases.iter().sum::<usize>() as f64;
+            let (own_max, total) = match plot_type {
+                "cumulative" => (total_bases, total_bases),
+                "bases" => (*bases.iter().max().unwrap_or(&0) as f64, total_bases),
+                _ => (*reads.iter().max().unwrap_or(&0) as f64, reads.iter().sum::<u64>() as f64),
+            };
+            scale.length = fold(scale.length, own_max, total);
+
+            let q = q_bins(&f.q_vector, quality_bins);
+            scale.qscore = fold(scale.qscore, *q.iter().max().unwrap_or(&0) as f64, q.iter().sum::<u64>() as f64);
+
+            let qb = qbases_bins(&f.q_hash, quality_bins);
+            let total = qb.iter().sum::<i64>() as f64;
+            let own_max = if plot_type == "cumulative" { total } else { *qb.iter().max().unwrap_or(&0) as f64 };
+            scale.qbases = fold(scale.qbases, own_max, total);
         }
+        scale
+    }
+
+    fn describe(&self) -> &'static str {
+        match self.mode.as_str() {
+            "shared" => "shared by all files",
+            "percent" => "percent of each file",
+            _ => "scaled per file",
+        }
+    }
+
+    // bar height in percent of the sparkline
+    fn height(&self, value: f64, own_max: f64, total: f64, shared: f64) -> f64 {
+        let height = match self.mode.as_str() {
+            "shared" => value / shared,
+            "percent" => value / total / shared,
+            _ => value / own_max,
+        };
+        if height.is_finite() { height * 100.0 } else { 0.0 }
+    }
+}
+
+fn q_bins(q_vector: &[u8], quality_bins: &bins::QualityBins) -> Vec<u64> {
+    let mut bins = vec![0; quality_bins.count];
+    for &q in q_vector {
+        bins[quality_bins.index(q as usize)] += 1; // the last bin takes out-of-range values
     }
+    bins
+}
+
+// mean and median read Qscore as vertical lines over a quality sparkline, none for fasta
+fn q_markers(f: &UploadedFile, quality_bins: &bins::QualityBins) -> String {
+    if f.q_hash.is_empty() || f.q_vector.is_empty() {
+        return String::new();
+    }
+    let mean = f.q_vector.iter().map(|&q| q as f64).sum::<f64>() / f.q_vector.len() as f64;
+    [("mean", mean), ("median", f.m_qscore as f64)]
+        .iter()
+        .map(|(name, q)| {
+            format!(
+                r#"<div class="q-marker q-{name}" style="left: {left:.1}%;" title="{name} read Q {q:.1}"></div>"#,
+                left = quality_bins.percent(*q + 0.5), // middle of the Q value, as in the plots
+            )
+        })
+        .collect()
+}
+
+fn generate_q_histogram(q_vector: &[u8], quality_bins: &bins::QualityBins, y_scale: &YScale) -> String {
+    let bins = q_bins(q_vector, quality_bins);
 
     // Find the maximum count to normalize the bar heights
     let max_count = *bins.iter().max().unwrap_or(&1);
@@ -416,16 +719,15 @@ fn generate_q_histogram(q_vector: &[u8]) -> String {
     bins.iter()
         .enumerate()
         .map(|(i, &count)| {
-            let height = (count as f64 / max_count as f64) * 100.0; // Normalize height to a percentage
+            let height = y_scale.height(count as f64, max_count as f64, sum_count as f64, y_scale.qscore);
             let percent = format!("{:.0}", count as f64 / sum_count as f64 * 100.0);
             format!(
                 r#"<div class="bar" style="height: {height}%;">
-                    <div class="tooltip">Q {range_start}-{range_end}: <br/>
+                    <div class="tooltip">Q {range}: <br/>
                     {count} reads ({percent}%)</div>
                 </div>"#,
                 height = height,
-                range_start = i * 2,
-                range_end = i * 2 + 2,
+                range = quality_bins.label(i),
                 count = count.human_count_bare()
             )
         })
@@ -433,21 +735,17 @@ fn generate_q_histogram(q_vector: &[u8]) -> String {
         .join("") // Combine all bars into a single string
 }
 
-fn generate_qbases_histogram(q_hash: &std::collections::BTreeMap<u8, i64>, plot_type: String) -> String {
-    let mut bins = [0i64; 30]; // 30 bins for Q 0-60 (2 per bin)
-    let max_bin_index = bins.len() - 1;
-
+fn qbases_bins(q_hash: &BTreeMap<u8, i64>, quality_bins: &bins::QualityBins) -> Vec<i64> {
+    let mut bins = vec![0i64; quality_bins.count];
     // Bin the bases by quality score (q_hash: quality -> bases count)
     for (&q, &count) in q_hash.iter() {
-        let phred = q.saturating_sub(33);
-        let bin = (phred / 2) as usize;
-        if bin < bins.len() {
-            bins[bin] += count;
-        } else {
-            bins[max_bin_index] += count;
-        }
+        bins[quality_bins.index(q.saturating_sub(33) as usize)] += count;
     }
+    bins
+}
 
+fn generate_qbases_histogram(q_hash: &BTreeMap<u8, i64>, plot_type: String, quality_bins: &bins::QualityBins, y_scale: &YScale) -> String {
+    let bins = qbases_bins(q_hash, quality_bins);
     let max_bases = *bins.iter().max().unwrap_or(&1);
     let total_bases: i64 = bins.iter().sum();
     let mut temp_total = total_bases;
@@ -464,7 +762,7 @@ fn generate_qbases_histogram(q_hash: &std::collections::BTreeMap<u8, i64>, plot_
 
             match plot_type.as_str() {
             "cumulative" => {
-                let height = (temp_total - bases) as f64 / total_bases as f64 * 100.0;
+                let height = y_scale.height((temp_total - bases) as f64, total_bases as f64, total_bases as f64, y_scale.qbases);
                 let percent = if total_bases > 0 {
                     format!("{:.1}", (temp_total - bases) as f64 / total_bases as f64 * 100.0)
                 } else {
@@ -478,13 +776,13 @@ fn generate_qbases_histogram(q_hash: &std::collections::BTreeMap<u8, i64>, plot_
                     {bases} bases ({percent}%)</div>
                 </div>"#,
                 height = height,
-                range_end = i * 2 + 2,
+                range_end = (i + 1) * quality_bins.width,
                 bases = (temp_total + bases).human_count_bare(),
                 percent = percent
                 )
             }
             _ => {
-                let height = (bases as f64 / max_bases as f64) * 100.0;
+                let height = y_scale.height(bases as f64, max_bases as f64, total_bases as f64, y_scale.qbases);
                 let percent = if total_bases > 0 {
                     format!("{:.1}", bases as f64 / total_bases as f64 * 100.0)
                 } else {
@@ -492,12 +790,11 @@ fn generate_qbases_histogram(q_hash: &std::collections::BTreeMap<u8, i64>, plot_
                 };
                 format!(
                 r#"<div class="bar" style="height: {height}%;">
-                    <div class="tooltip">Q {range_start}-{range_end}:<br/>
+                    <div class="tooltip">Q {range}:<br/>
                     {bases} bases ({percent}%)</div>
                 </div>"#,
                 height = height,
-                range_start = i * 2,
-                range_end = i * 2 + 2,
+                range = quality_bins.label(i),
                 bases = bases.human_count_bare(),
                 percent = percent
                 )
@@ -508,38 +805,8 @@ fn generate_qbases_histogram(q_hash: &std::collections::BTreeMap<u8, i64>, plot_
         .join("")
 }
 
-fn generate_l_histogram(l_vector: &[i64], binsize: usize, plot_type: String) -> String {
-    let mut bins = [0; 30]; // Create 30 bins for the histogram
-    let max_bin_index = bins.len() - 1; // Index of the last bin
-
-    for &l in l_vector {
-        let bin = l as usize / binsize; // 1 bin spans binsize bp
-        if bin < bins.len() {
-            bins[bin] += 1;
-        } else {
-            bins[max_bin_index] += 1; // Increment the last bin for out-of-range values
-        }
-    }
-
-    // Calculate bases per bin
-    let bases_per_bin: Vec<usize> = bins.iter().enumerate()
-        .map(|(i, _)| {
-            if i == max_bin_index {
-                // Last bin: from (max_bin_index * binsize) to infinity
-                l_vector.iter()
-                    .filter(|&&l| (l as usize) >= max_bin_index * binsize)
-                    .map(|&l| l as usize)
-                    .sum()
-            } else {
-                // Bin: from (i * binsize) to ((i+1) * binsize)
-                l_vector.iter()
-                    .filter(|&&l| (l as usize) >= i * binsize && (l as usize) < (i + 1) * binsize)
-                    .map(|&l| l as usize)
-                    .sum()
-            }
-        })
-        .collect();
-
+fn generate_l_histogram(l_vector: &[i64], length_bins: &bins::LengthBins, plot_type: String, y_scale: &YScale) -> String {
+    let (bins, bases_per_bin) = length_bins.count(l_vector);
 
     // Find the maximum count to normalize the bar heights
     let reads_max_bin = *bins.iter().max().unwrap_or(&1); // note sparkline bar is normalised relative to the max count, not sum count
@@ -557,17 +824,13 @@ fn generate_l_histogram(l_vector: &[i64], binsize: usize, plot_type: String) ->
             let bases = bases_per_bin[i];
             let bases_percent = format!("{:.1}", bases as f64 / total_bases as f64 * 100.0);
 
-            let range_start = i * binsize;
-            let range = if i == max_bin_index {
-                format!(">{}", range_start.human_count_bare()) // Use ">range_start" for the last bin
-            } else {
-                format!("{}-{}", range_start.human_count_bare(), (range_start + binsize).human_count_bare())
-            };
+            let range_start = length_bins.start(i);
+            let range = length_bins.label(i);
 
             // specific sparklines here 
             match plot_type.as_str() {
                 "cumulative" => {
-                    let height = (temp_total_bases as f64 / total_bases as f64) * 100.0;
+                    let height = y_scale.height(temp_total_bases as f64, total_bases as f64, total_bases as f64, y_scale.length);
                     let percent = if temp_total_bases > 0 {
                         format!("{:.1}", temp_total_bases as f64 / total_bases as f64 * 100.0)
                     } else {
@@ -587,7 +850,7 @@ fn generate_l_histogram(l_vector: &[i64], binsize: usize, plot_type: String) ->
                     )
                 }
                 "bases" => {
-                    let height = (bases as f64 / bases_max_bin as f64) * 100.0;
+                    let height = y_scale.height(bases as f64, bases_max_bin as f64, total_bases as f64, y_scale.length);
                     let percent = if total_reads > 0 {
                         reads_percent.clone()
                     } else {
@@ -608,7 +871,7 @@ fn generate_l_histogram(l_vector: &[i64], binsize: usize, plot_type: String) ->
                     )
                 }
                 _ => {
-                    let height = (reads_count as f64 / reads_max_bin as f64) * 100.0;
+                    let height = y_scale.height(reads_count as f64, reads_max_bin as f64, total_reads as f64, y_scale.length);
                     let percent = if total_reads > 0 {
                         reads_percent.clone()
                     } else {
@@ -635,9 +898,29 @@ fn generate_l_histogram(l_vector: &[i64], binsize: usize, plot_type: String) ->
 }
 
 fn app() -> Element {
-    //let mut enable_directory_upload = use_signal(|| false);
     let mut numbers = use_signal(|| "none".to_string());
     let mut basesperbin = use_signal(|| 1000);
+    let mut length_scale = use_signal(|| "auto".to_string()); // fixed, auto or log
+    let mut bin_count = use_signal(|| bins::DEFAULT_COUNT);
+    let mut y_mode = use_signal(|| "own".to_string()); // own, shared or percent
+    let mut q_scale = use_signal(|| "auto".to_string()); // auto or fixed
+    let mut q_width = use_signal(|| 2);
+    let mut q_max = use_signal(|| 60);
+    let mut show_charts = use_signal(|| false);
+    let chart_file = use_signal(String::new);
+    let mut chart_zoom = use_signal(|| None::<(f64, f64)>); // length range of the plots
+    let chart_brush = use_signal(|| None::<(f64, f64)>); // drag start and end, px into the plot area
+    let chart_readout = use_signal(String::new);
+    let mut selected_rows = use_signal(std::collections::BTreeSet::<String>::new);
+    let mut show_compare = use_signal(|| false);
+    let compare_normalise = use_signal(|| true);
+    let mut compare_zoom = use_signal(|| None::<(f64, f64)>);
+    let compare_brush = use_signal(|| None::<(f64, f64)>);
+    let compare_readout = use_signal(String::new);
+    let mut detail_file = use_signal(|| None::<String>); // row shown on the detail page
+    let mut detail_zoom = use_signal(|| None::<(f64, f64)>);
+    let detail_brush = use_signal(|| None::<(f64, f64)>);
+    let detail_readout = use_signal(String::new);
     let mut spark_type = use_signal(|| "reads".to_string()); // Default to "reads"
     let mut name_type_sig = use_signal(|| "basename".to_string());
     let mut files_uploaded = use_signal(|| Vec::new() as Vec<UploadedFile>);
@@ -660,72 +943,159 @@ fn app() -> Element {
     let mut progress_percentage = use_signal(|| 0.0);
     let mut show_popup = use_signal(|| false);
     let mut sort_by = use_signal(|| ("name".to_string(), true)); // Default sort by name ascending
+    let mut show_indexes = use_signal(|| false);
+    let mut folder_preview = use_signal(|| None as Option<(sources::FileSource, Vec<String>)>);
+    let folder_include = use_signal(String::new);
+    let folder_exclude = use_signal(String::new);
+    let mut dragging = use_signal(|| false);
+    let mut file_sources = use_signal(BTreeMap::<String, sources::FileSource>::new);
+    let mut show_filter = use_signal(|| false);
+    let read_filter = use_signal(filter::ReadFilter::default);
+    let mut show_subsample = use_signal(|| false);
+    let subsample_settings = use_signal(subsample::Subsample::default);
+    let mut show_browser = use_signal(|| false);
+    let browser_file = use_signal(String::new);
+    let mut browser_index = use_signal(|| None as Option<std::sync::Arc<reads::ReadIndex>>);
+    let mut browser_loading = use_signal(|| false);
+    let browser_page = use_signal(|| 0usize);
+    let browser_limits = use_signal(|| (0u32, 0u8));
+    let mut show_search = use_signal(|| false);
+    let search_motifs = use_signal(String::new);
+    let search_mismatches = use_signal(|| 0usize);
+    let mut search_results = use_signal(Vec::<search::MotifResult>::new);
+    let mut show_screen = use_signal(|| false);
+    let mut references = use_signal(screen::ReferenceIndex::default);
+    let mut screen_refs = use_signal(Vec::<String>::new);
+    let mut show_similarity = use_signal(|| false);
+    let mut saved_sketches = use_signal(Vec::<sketch::NamedSketch>::new);
+    let mut file_sketches = use_signal(Vec::<sketch::NamedSketch>::new); // made on request, not while loading
+    let similarity_threshold = use_signal(|| 0.5);
+    let mut sketch_status = use_signal(String::new);
+    let mut species_db = use_signal(sketch::SketchDb::default);
+    let mut species_results = use_signal(Vec::<(String, Vec<sketch::Match>)>::new);
+    let mut species_status = use_signal(String::new);
+    // sketches of the loaded files and the imported ones, in cluster order
+    let similarity = use_memo(move || {
+        if !show_similarity() {
+            return (Vec::new(), Vec::new());
+        }
+        let mut sketches: Vec<sketch::NamedSketch> = file_sketches.read().iter().filter(|s| !s.sketch.is_empty()).cloned().collect();
+        sketches.extend(saved_sketches.read().iter().cloned());
+        let matrix = sketch::similarity_matrix(&sketches);
+        let order = sketch::cluster_order(&matrix);
+        let matrix = order.iter().map(|&i| order.iter().map(|&j| matrix[i][j]).collect()).collect();
+        (order.into_iter().map(|i| sketches[i].clone()).collect(), matrix)
+    });
+    let mut export_status = use_signal(String::new);
+    let mut export_outputs = use_signal(std::collections::BTreeSet::<String>::new); // files written by filter/subsample
+    let samplesheet = use_signal(String::new);
+    let mut group_by = use_signal(|| "none".to_string());
+    let mut group_regex = use_signal(String::new);
+    let expanded_groups = use_signal(std::collections::BTreeSet::<String>::new);
+    let file_groups = use_memo(move || groups::group_files(&files_uploaded.read(), &group_by(), &group_regex.read()));
+    // names of the files in table order, then of the pooled group rows
+    let table_rows = use_memo(move || {
+        let (column, ascending) = sort_by.read().clone();
+        let files = files_uploaded.read();
+        let mut order: Vec<&UploadedFile> = files.iter().collect();
+        order.sort_by(|a, b| {
+            let order = compare_files(a, b, &column);
+            if ascending { order } else { order.reverse() }
+        });
+        let mut names: Vec<String> = order.iter().map(|f| f.name.clone()).collect();
+        names.extend(file_groups.read().iter().filter(|g| g.members.len() > 1).map(|g| g.pooled.name.clone()));
+        names
+    });
+    // pooled statistics of the ticked rows, or of all files when none is ticked
+    let total_row = use_memo(move || {
+        let selected = selected_rows.read();
+        let files = files_uploaded.read();
+        if selected.is_empty() {
+            let label = format!("Total ({} files)", files.len());
+            return groups::pool_files(&label, &label, &files.iter().collect::<Vec<_>>());
+        }
+        // a ticked group stands for its members
+        let groups = file_groups.read();
+        let members: std::collections::BTreeSet<&str> = groups
+            .iter()
+            .filter(|g| g.members.len() > 1 && selected.contains(&g.pooled.name))
+            .flat_map(|g| g.members.iter().map(|m| m.name.as_str()))
+            .collect();
+        let chosen: Vec<&UploadedFile> = table_rows
+            .read()
+            .iter()
+            .filter(|name| selected.contains(*name) && !members.contains(name.as_str()))
+            .filter_map(|name| groups::find_row(&files, &groups, name))
+            .collect();
+        let label = format!("Selected ({} rows)", chosen.len());
+        groups::pool_files(&label, &label, &chosen)
+    });
+    let compared = use_memo(move || {
+        let selected = selected_rows.read();
+        let (files, groups) = (files_uploaded.read(), file_groups.read());
+        table_rows
+            .read()
+            .iter()
+            .filter(|name| selected.contains(*name))
+            .filter_map(|name| groups::find_row(&files, &groups, name).cloned())
+            .collect::<Vec<UploadedFile>>()
+    });
+    let length_bins = use_memo(move || bins::LengthBins::for_files(&length_scale(), basesperbin(), bin_count(), &files_uploaded.read()));
+    let quality_bins = use_memo(move || bins::QualityBins::for_files(&q_scale(), q_width(), q_max(), &files_uploaded.read()));
+    // pooled group rows are drawn on the same scale as the files
+    let y_scale = use_memo(move || {
+        let groups = file_groups.read();
+        let files = files_uploaded.read();
+        YScale::for_files(&y_mode(), files.iter().chain(groups.iter().map(|g| &g.pooled)), &length_bins.read(), &quality_bins.read(), &spark_type())
+    });
+    let sparklines = use_memo(move || Sparklines {
+        length_bins: length_bins(),
+        quality_bins: quality_bins(),
+        y_scale: y_scale(),
+        plot_type: spark_type(),
+    });
+    let export_detail = move |_| {
+        let (files, groups) = (files_uploaded.read(), file_groups.read());
+        if let Some(f) = detail_file.read().as_ref().and_then(|name| groups::find_row(&files, &groups, name)) {
+            let html = detail::to_html(f, &length_bins.read(), &spark_type(), &screen_refs.read());
+            let file_name = format!("{}_detail.html", sources::file_stem(&f.basename));
+            save_file("Save HTML File", &file_name, html.into_bytes(), "text/html");
+        }
+    };
 
-    let read_files = move |file_engine: Arc<dyn dioxus_elements::FileEngine>| async move {
-        let files = file_engine.files();
+    let read_files = move |source: sources::FileSource, files: Vec<String>| async move {
         for file in &files {
             if *cancel_processing.read() {
                 break; // Exit the loop if processing is canceled
             }
 
-            let mut nreads: u64 = 0;
-            let mut nbases: u64 = 0;
-            let mut gcbases: u64 = 0;
-            let mut qual20: i64 = 0;
-            let mut qual30: i64 = 0;
-            let mut len_vector: Vec<i64> = Vec::new();
-            let mut qual_vector: Vec<u8> = Vec::new();
-            let mut qhash: BTreeMap<u8, i64> = BTreeMap::new();
-
-            if let Some(bytes) = file_engine.read_file(&file).await {
-                let filepath = Path::new(&file);
-                let basename = filepath.file_name().unwrap().to_str().unwrap();
+            if let Some(bytes) = source.read(file).await {
                 let reader = decode_reader(bytes, file).await.unwrap();
-                let mut recs = fastq::Reader::new(reader).records();
+                let recs = sources::records(reader, sources::is_fasta(file));
+                let mut stats = stats::FileStats::new(file);
 
                 let mut record_counter = 0;
                 let start_reads = *total_reads.read();
                 let start_bases = *total_bases.read();
-                while let Some(Ok(rec)) = recs.next() {
+                for rec in recs {
                     if *cancel_processing.read() {
                         break; // Exit the loop if processing is canceled
                     }
-                    nreads += 1;
-                    gcbases += modules::get_gc_bases(rec.seq());
-                    nbases += rec.seq().len() as u64;
-                    qual20 += modules::get_qual_bases(rec.qual(), 53); // 33 offset
-                    qual30 += modules::get_qual_bases(rec.qual(), 63); // 33 offset
-                    len_vector.push(rec.seq().len() as i64);
-                    qual_vector.push(modules::qscore_mean(rec.qual()));
-                    for q in rec.qual().to_owned() {
-                        *qhash.entry(q).or_insert(0) += 1; // Count occurrences of each quality score
-                    }
+                    stats.add(&rec);
 
                     record_counter += 1;
                     if record_counter % 1000 == 0 {
-                        total_reads.set(start_reads + nreads);
-                        total_bases.set(start_bases + nbases);
+                        total_reads.set(start_reads + stats.nreads);
+                        total_bases.set(start_bases + stats.nbases);
                         my_yield().await; // Yield every 1k records to keep UI responsive
                     }
                 }
                 my_yield().await; // Final yield after finishing a file
-                let n50 = modules::get_nx(&mut len_vector, 0.5);
-                let median_qscore = modules::median(&mut qual_vector);
-
-                files_uploaded.write().push(UploadedFile {
-                    name: file.clone(),
-                    basename: basename.to_string(),
-                    reads: nreads,
-                    bases: nbases,
-                    q20: format!("{:.2}", qual20 as f64 / nbases as f64 * 100.0),
-                    q30: format!("{:.2}", qual30 as f64 / nbases as f64 * 100.0),
-                    nx: n50 as u64,
-                    gc: format!("{:.2}", gcbases as f64 / nbases as f64 * 100.0),
-                    m_qscore: median_qscore,
-                    q_vector: qual_vector.clone(),
-                    l_vector: len_vector.clone(),
-                    q_hash: qhash.clone(),
-                });
+                let (nreads, nbases) = (stats.nreads, stats.nbases);
+
+                files_uploaded.write().push(stats.finish());
+                pair_inserts(files_uploaded).await;
+                file_sources.write().insert(file.clone(), source.clone());
                 let prev_count = *files_count_post.read();
                 files_count_post.set(prev_count + 1); // increment after each file processed
                 progress_percentage.set((*files_count_post.read() as f64 / *files_count_pre.read() as f64) * 100.0);
@@ -757,11 +1127,11 @@ fn app() -> Element {
     };
 
 
-    let upload_files = move |evt: FormEvent| {
-        if let Some(file_engine) = evt.files() {
+    let mut process_files = move |source: sources::FileSource, files: Vec<String>| {
+        if !files.is_empty() {
             cancel_processing.set(false); // Reset cancel status for a new upload
             busy.set(true);
-            files_count_pre.set(file_engine.files().len());
+            files_count_pre.set(files.len());
             files_count_post.set(0);
             #[cfg(not(target_arch = "wasm32"))]
             start_time.set(Instant::now()); // Record the start time
@@ -770,7 +1140,7 @@ fn app() -> Element {
             let start_time_web = web_sys::window().unwrap().performance().unwrap().now();
             
             spawn(async move { 
-                read_files(file_engine).await; // Process all files
+                read_files(source, files).await; // Process all files
                 
                 #[cfg(not(target_arch = "wasm32"))]
                 myduration.set(HumanDuration(Instant::now() - start_time()).to_string()); 
@@ -792,6 +1162,482 @@ fn app() -> Element {
             });
         } 
     };
+
+    let upload_files = move |evt: FormEvent| {
+        if let Some(file_engine) = evt.files() {
+            let files = file_engine.files();
+            process_files(sources::FileSource::Engine(file_engine), files);
+        }
+    };
+
+    // folder selection: discovered files are previewed and filtered before loading
+    let upload_folder = move |evt: FormEvent| {
+        if let Some(file_engine) = evt.files() {
+            let files = file_engine.files().into_iter().filter(|f| sources::is_sequence_file(f)).collect();
+            folder_preview.set(Some((sources::FileSource::Engine(file_engine), files)));
+        }
+    };
+    // write the selected reads of every loaded file to new gzipped fastq files,
+    // the outputs are added to the table next to their inputs
+    let export_reads = move |selector: exports::ReadSelector| {
+        spawn(async move {
+            #[cfg(not(target_arch = "wasm32"))]
+            let dir = match task::spawn_blocking(|| FileDialog::new().set_title("Save reads to folder").pick_folder()).await {
+                Ok(Some(dir)) => dir,
+                _ => return,
+            };
+
+            // earlier outputs are loaded too, but only the source files are exported
+            let inputs: Vec<(String, exports::FileSelector)> = files_uploaded
+                .read()
+                .iter()
+                .filter(|f| !export_outputs.read().contains(&f.name))
+                .map(|f| (f.name.clone(), selector.for_file(f)))
+                .collect();
+            cancel_processing.set(false);
+            busy.set(true);
+            files_count_pre.set(inputs.len());
+            files_count_post.set(0);
+            let (mut reads_in, mut reads_out, mut bases_in, mut bases_out) = (0u64, 0u64, 0u64, 0u64);
+            let mut errors = Vec::new();
+
+            for (input, mut file_selector) in inputs {
+                if *cancel_processing.read() {
+                    break;
+                }
+                let source = file_sources.read().get(&input).cloned();
+                let Some(bytes) = (match source {
+                    Some(source) => source.read(&input).await,
+                    None => None,
+                }) else {
+                    continue;
+                };
+                let reader = decode_reader(bytes, &input).await.unwrap();
+                let out_name = exports::output_name(&input, selector.suffix());
+                let mut output = exports::FastqOutput::new(&out_name);
+                let mut failed = None;
+
+                for (i, rec) in sources::records(reader, sources::is_fasta(&input)).enumerate() {
+                    reads_in += 1;
+                    bases_in += rec.seq().len() as u64;
+                    if let Some(selected) = file_selector.select(&rec) {
+                        reads_out += 1;
+                        bases_out += selected.seq().len() as u64;
+                        if let Err(e) = output.write(&selected) {
+                            failed = Some(e);
+                            break;
+                        }
+                    }
+                    if i % 1000 == 0 {
+                        my_yield().await;
+                    }
+                }
+
+                let (bytes, stats) = match failed.map_or_else(|| output.finish(), Err) {
+                    Ok(finished) => finished,
+                    Err(e) => {
+                        errors.push(format!("{}: {}", out_name, e));
+                        continue;
+                    }
+                };
+                #[cfg(not(target_arch = "wasm32"))]
+                let stats = match exports::write_to_dir(&dir, &out_name, &bytes) {
+                    Ok(path) => {
+                        file_sources.write().insert(path.clone(), sources::FileSource::Disk);
+                        UploadedFile { name: path, ..stats }
+                    }
+                    Err(e) => {
+                        errors.push(format!("{}: {}", out_name, e));
+                        continue;
+                    }
+                };
+                #[cfg(target_arch = "wasm32")]
+                {
+                    exports::download(&out_name, &bytes, "application/gzip");
+                    file_sources.write().insert(stats.name.clone(), sources::FileSource::Memory(std::sync::Arc::new(bytes)));
+                }
+                total_reads.set(total_reads() + stats.reads);
+                total_bases.set(total_bases() + stats.bases);
+                export_outputs.write().insert(stats.name.clone());
+                files_uploaded.write().push(stats);
+                pair_inserts(files_uploaded).await;
+                files_count_post.set(files_count_post() + 1);
+                progress_percentage.set(files_count_post() as f64 / files_count_pre() as f64 * 100.0);
+            }
+
+            let mut status = format!(
+                "{}: kept {} of {} reads ({:.1}%) and {} of {} bases ({:.1}%)",
+                selector.describe(),
+                HumanCount(reads_out), HumanCount(reads_in), reads_out as f64 / reads_in.max(1) as f64 * 100.0,
+                HumanCount(bases_out), HumanCount(bases_in), bases_out as f64 / bases_in.max(1) as f64 * 100.0,
+            );
+            if !errors.is_empty() {
+                status.push_str(&format!("; failed to write {}", errors.join(", ")));
+            }
+            export_status.set(status);
+            busy.set(false);
+            progress_percentage.set(0.0);
+        });
+    };
+
+    // index where the records of the chosen file start, for the read browser
+    let open_in_browser = move |name: String| {
+        spawn(async move {
+            browser_index.set(None);
+            let source = file_sources.read().get(&name).cloned();
+            let Some(source) = source else {
+                return;
+            };
+            browser_loading.set(true);
+            my_yield().await;
+            if let Some(bytes) = source.read(&name).await {
+                let (gz, fasta) = (name.ends_with(".gz"), sources::is_fasta(&name));
+                #[cfg(not(target_arch = "wasm32"))]
+                let index = task::spawn_blocking(move || reads::ReadIndex::build(bytes, gz, fasta)).await.ok();
+                #[cfg(target_arch = "wasm32")]
+                let index = Some(reads::ReadIndex::build(bytes, gz, fasta));
+                if let Some(index) = index {
+                    browser_index.set(Some(std::sync::Arc::new(index)));
+                }
+            }
+            browser_loading.set(false);
+        });
+    };
+
+    // records of the shown browser page, parsed off the UI task
+    let browser_records = use_resource(move || async move {
+        let Some(index) = browser_index() else {
+            return Vec::new();
+        };
+        let (limits, page) = (browser_limits(), browser_page());
+        #[cfg(not(target_arch = "wasm32"))]
+        let records = task::spawn_blocking(move || index.page(limits, page)).await.unwrap_or_default();
+        #[cfg(target_arch = "wasm32")]
+        let records = index.page(limits, page);
+        records
+    });
+
+    // count reads containing each motif, file by file
+    let run_search = move |_| {
+        spawn(async move {
+            let motifs = search::parse_motifs(&search_motifs.read());
+            let mismatches = search_mismatches();
+            let inputs: Vec<String> = files_uploaded.read().iter().map(|f| f.name.clone()).collect();
+            search_results.set(Vec::new());
+            cancel_processing.set(false);
+            busy.set(true);
+            files_count_pre.set(inputs.len());
+            files_count_post.set(0);
+
+            for input in inputs {
+                if *cancel_processing.read() {
+                    break;
+                }
+                let source = file_sources.read().get(&input).cloned();
+                let Some(bytes) = (match source {
+                    Some(source) => source.read(&input).await,
+                    None => None,
+                }) else {
+                    continue;
+                };
+                let reader = decode_reader(bytes, &input).await.unwrap();
+                let basename = Path::new(&input).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(input.clone());
+                let mut results: Vec<search::MotifResult> = motifs.iter().map(|m| search::MotifResult::new(&basename, m)).collect();
+
+                for (i, rec) in sources::records(reader, sources::is_fasta(&input)).enumerate() {
+                    for (result, motif) in results.iter_mut().zip(motifs.iter()) {
+                        result.add(rec.seq(), motif, mismatches);
+                    }
+                    if i % 1000 == 0 {
+                        if *cancel_processing.read() {
+                            break;
+                        }
+                        my_yield().await;
+                    }
+                }
+                search_results.write().extend(results);
+                files_count_post.set(files_count_post() + 1);
+                progress_percentage.set(files_count_post() as f64 / files_count_pre() as f64 * 100.0);
+            }
+            busy.set(false);
+            progress_percentage.set(0.0);
+        });
+    };
+
+    // each fasta file is one reference, all its sequences are indexed together
+    let add_references = move |evt: FormEvent| {
+        if let Some(file_engine) = evt.files() {
+            spawn(async move {
+                for name in file_engine.files() {
+                    let Some(bytes) = file_engine.read_file(&name).await else {
+                        continue;
+                    };
+                    let Ok(reader) = decode_reader(bytes, &name).await else {
+                        continue;
+                    };
+                    let seqs: Vec<Vec<u8>> = sources::records(reader, true).map(|rec| rec.seq().to_vec()).collect();
+                    let label = sources::file_stem(&name);
+                    references.write().add(&label, &seqs);
+                    my_yield().await;
+                }
+            });
+        }
+    };
+
+    // sketch the k-mers of every loaded file, dropping those seen once in reads
+    let run_sketches = move |_| {
+        spawn(async move {
+            let inputs: Vec<String> = files_uploaded.read().iter().map(|f| f.name.clone()).collect();
+            file_sketches.set(Vec::new());
+            cancel_processing.set(false);
+            busy.set(true);
+            files_count_pre.set(inputs.len());
+            files_count_post.set(0);
+
+            for input in inputs {
+                if *cancel_processing.read() {
+                    break;
+                }
+                let source = file_sources.read().get(&input).cloned();
+                let Some(bytes) = (match source {
+                    Some(source) => source.read(&input).await,
+                    None => None,
+                }) else {
+                    continue;
+                };
+                let reader = decode_reader(bytes, &input).await.unwrap();
+                // genomes keep every k-mer, like in "fasterx sketch"
+                let fasta = sources::is_fasta(&input);
+                let (mut genome, mut read_sketch) = (sketch::Sketch::default(), sketch::ReadSketch::default());
+                let mut reads = 0;
+                for (i, rec) in sources::records(reader, fasta).enumerate() {
+                    if fasta {
+                        genome.add(rec.seq());
+                    } else {
+                        read_sketch.add(rec.seq());
+                    }
+                    reads += 1;
+                    if i % 1000 == 0 {
+                        if *cancel_processing.read() {
+                            break;
+                        }
+                        my_yield().await;
+                    }
+                }
+                let basename = Path::new(&input).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(input.clone());
+                file_sketches.write().push(sketch::NamedSketch { name: basename, reads, saved: false, sketch: if fasta { genome } else { read_sketch.sketch } });
+                files_count_post.set(files_count_post() + 1);
+                progress_percentage.set(files_count_post() as f64 / files_count_pre() as f64 * 100.0);
+            }
+            busy.set(false);
+            progress_percentage.set(0.0);
+        });
+    };
+
+    let import_sketches = move |evt: FormEvent| {
+        if let Some(file_engine) = evt.files() {
+            spawn(async move {
+                for name in file_engine.files() {
+                    let Some(text) = file_engine.read_file_to_string(&name).await else {
+                        continue;
+                    };
+                    match sketch::from_json(&text) {
+                        Ok(sketches) => {
+                            sketch_status.set(format!("Imported {} sketches from {}", sketches.len(), name));
+                            saved_sketches.write().extend(sketches);
+                        }
+                        Err(e) => sketch_status.set(format!("{}: {}", name, e)),
+                    }
+                }
+            });
+        }
+    };
+
+    let export_sketches = move |_| {
+        let sketches: Vec<sketch::NamedSketch> = similarity.read().0.iter().filter(|s| !s.saved).cloned().collect();
+        save_file("Save sketches", "fasterx_sketches.json", sketch::to_json(&sketches).into_bytes(), "application/json");
+        sketch_status.set(format!("Exported {} sketches", sketches.len()));
+    };
+
+    // sketch databases are json files written by "fasterx sketch" or exported from the app
+    let add_db_files = move |evt: FormEvent| {
+        if let Some(file_engine) = evt.files() {
+            spawn(async move {
+                for name in file_engine.files() {
+                    let Some(text) = file_engine.read_file_to_string(&name).await else {
+                        continue;
+                    };
+                    match sketch::from_json(&text) {
+                        Ok(sketches) => species_db.write().add(sketches),
+                        Err(e) => species_status.set(format!("{}: {}", name, e)),
+                    }
+                }
+            });
+        }
+    };
+
+    let add_db_folder = move |_evt: MouseEvent| {
+        #[cfg(not(target_arch = "wasm32"))]
+        spawn(async move {
+            let dir = task::spawn_blocking(|| FileDialog::new().set_title("Sketch database folder").pick_folder()).await;
+            if let Ok(Some(dir)) = dir {
+                match task::spawn_blocking(move || sketch::load_dir(&dir)).await {
+                    Ok(Ok(sketches)) => species_db.write().add(sketches),
+                    Ok(Err(e)) => species_status.set(e),
+                    Err(e) => species_status.set(e.to_string()),
+                }
+            }
+        });
+    };
+
+    // all k-mers of every file are looked up in the reference sketches
+    let run_species = move |_| {
+        spawn(async move {
+            let inputs: Vec<String> = files_uploaded.read().iter().map(|f| f.name.clone()).collect();
+            species_results.set(Vec::new());
+            species_status.set(String::new());
+            cancel_processing.set(false);
+            busy.set(true);
+            files_count_pre.set(inputs.len());
+            files_count_post.set(0);
+
+            for input in inputs {
+                if *cancel_processing.read() {
+                    break;
+                }
+                let source = file_sources.read().get(&input).cloned();
+                let Some(bytes) = (match source {
+                    Some(source) => source.read(&input).await,
+                    None => None,
+                }) else {
+                    continue;
+                };
+                let reader = decode_reader(bytes, &input).await.unwrap();
+                let mut found = std::collections::HashSet::new();
+                for (i, rec) in sources::records(reader, sources::is_fasta(&input)).enumerate() {
+                    species_db.read().screen(&mut found, rec.seq());
+                    if i % 1000 == 0 {
+                        if *cancel_processing.read() {
+                            break;
+                        }
+                        my_yield().await;
+                    }
+                }
+                let basename = Path::new(&input).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or(input.clone());
+                let matches = species_db.read().top_matches(&found, 5);
+                species_results.write().push((basename, matches));
+                files_count_post.set(files_count_post() + 1);
+                progress_percentage.set(files_count_post() as f64 / files_count_pre() as f64 * 100.0);
+            }
+            busy.set(false);
+            progress_percentage.set(0.0);
+        });
+    };
+
+    // screen a random sample of the reads of every file against the loaded references
+    let run_screen = move |_| {
+        spawn(async move {
+            let inputs: Vec<(String, u64)> = files_uploaded.read().iter().map(|f| (f.name.clone(), f.reads)).collect();
+            cancel_processing.set(false);
+            busy.set(true);
+            files_count_pre.set(inputs.len());
+            files_count_post.set(0);
+
+            for (input, reads) in inputs {
+                if *cancel_processing.read() {
+                    break;
+                }
+                let source = file_sources.read().get(&input).cloned();
+                let Some(bytes) = (match source {
+                    Some(source) => source.read(&input).await,
+                    None => None,
+                }) else {
+                    continue;
+                };
+                let reader = decode_reader(bytes, &input).await.unwrap();
+                let mut counts = references.read().new_counts();
+                // reads from the whole run, the first ones are not representative
+                let keep = subsample::reservoir(reads as usize, screen::SCREEN_SAMPLE, screen::SCREEN_SEED);
+                for (i, rec) in sources::records(reader, sources::is_fasta(&input)).enumerate() {
+                    if keep.get(i).copied().unwrap_or(false) {
+                        references.read().screen(&mut counts, rec.seq());
+                    }
+                    if i % 1000 == 0 {
+                        if *cancel_processing.read() {
+                            break;
+                        }
+                        my_yield().await;
+                    }
+                }
+                if let Some(f) = files_uploaded.write().iter_mut().find(|f| f.name == input) {
+                    f.screen = counts;
+                }
+                files_count_post.set(files_count_post() + 1);
+                progress_percentage.set(files_count_post() as f64 / files_count_pre() as f64 * 100.0);
+            }
+            screen_refs.set(references.read().names.clone());
+            busy.set(false);
+            progress_percentage.set(0.0);
+        });
+    };
+
+    // dropped files go the same way as the file input; on desktop dropped folders are
+    // searched for sequence files off the UI task and previewed like "Add Folder"
+    let drop_files = move |evt: DragEvent| {
+        evt.prevent_default();
+        dragging.set(false);
+        let Some(file_engine) = evt.files() else {
+            return;
+        };
+        let dropped = file_engine.files();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if dropped.iter().any(|f| Path::new(f).is_dir()) {
+                spawn(async move {
+                    let files = task::spawn_blocking(move || {
+                        let mut files = Vec::new();
+                        for f in dropped {
+                            if Path::new(&f).is_dir() {
+                                files.extend(sources::discover(Path::new(&f)));
+                            } else if sources::is_sequence_file(&f) {
+                                files.push(f);
+                            }
+                        }
+                        files
+                    })
+                    .await
+                    .unwrap_or_default();
+                    folder_preview.set(Some((sources::FileSource::Disk, files)));
+                });
+                return;
+            }
+        }
+        // the browser lists a dropped folder as a single file, its contents come from the drop entries
+        #[cfg(target_arch = "wasm32")]
+        {
+            let entries = evt.data().downcast::<web_sys::DragEvent>().map(sources::dropped_entries).unwrap_or_default();
+            if entries.iter().any(|entry| entry.is_directory()) {
+                spawn(async move {
+                    let found = sources::walk_entries(entries).await;
+                    let files = found.iter().map(|(path, _)| path.clone()).collect();
+                    folder_preview.set(Some((sources::FileSource::Dropped(std::sync::Arc::new(found)), files)));
+                });
+                return;
+            }
+        }
+        let files = dropped.into_iter().filter(|f| sources::is_sequence_file(f)).collect();
+        process_files(sources::FileSource::Engine(file_engine), files);
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let pick_folder = move |_| {
+        spawn(async move {
+            let dir = task::spawn_blocking(|| FileDialog::new().set_title("Add folder").pick_folder()).await;
+            if let Ok(Some(dir)) = dir {
+                let files = task::spawn_blocking(move || sources::discover(&dir)).await.unwrap_or_default();
+                folder_preview.set(Some((sources::FileSource::Disk, files)));
+            }
+        });
+    };
     
     rsx! {
         document::Stylesheet { href: "https://fonts.googleapis.com/css?family=Roboto:300,400,500" }
@@ -800,299 +1646,603 @@ fn app() -> Element {
             {include_str!("../assets/custom.css")} 
         }
         
-        {components::app_title(files_count_post)}
-        
         div {
-            class: "controls-row",
-            // Always show the upload button
-            label {
-                r#for: "textreader",
-                class: "file-upload-label",
-                svg {
-                    class: "upload-icon",
-                    fill: "none",
-                    stroke: "currentColor",
-                    view_box: "0 0 24 24",
-                    xmlns: "http://www.w3.org/2000/svg",
-                    path {
-                        stroke_linecap: "round",
-                        stroke_linejoin: "round",
-                        stroke_width: "2",
-                        d: "M7 16a4 4 0 01-.88-7.903A5 5 0 1115.9 6L16 6a5 5 0 011 9.9M15 13l-3-3m0 0l-3 3m3-3v12"
-                    }
+            class: if dragging() { "drop-target dragging" } else { "drop-target" },
+            ondragover: move |evt| {
+                evt.prevent_default();
+                dragging.set(true);
+            },
+            ondragleave: move |_| dragging.set(false),
+            ondrop: drop_files,
+
+            if dragging() {
+                div {
+                    id: "drop-zone",
+                    "Drop fastq/fasta files or folders to add them"
                 }
-                span { class: "upload-text", "Add Sequences" } 
-            }
-            input {
-                id: "textreader",
-                class: "file-input-hidden",
-                r#type: "file",
-                accept: ".fastq,.gz",
-                multiple: true,
-                onchange: upload_files
             }
 
-            // Only show other controls if files are uploaded
-            if files_uploaded.len() > 0 {
-                button {
-                    class: "usercontrols",
-                    onclick: move |_| {
-                        files_uploaded.write().clear();
-                        total_bases.set(0);
-                        total_reads.set(0);
-                        files_count_pre.set(0);
-                        files_count_post.set(0);
-                        name_type_sig.set("basename".to_string());
-                        progress_percentage.set(0.0);
-                    },
-                    "Clear"
+            {components::app_title(files_count_post)}
+
+
+            if let Some((source, files)) = folder_preview() {
+                {components::folder_preview(files, folder_include, folder_exclude, move |selected: Vec<String>| {
+                    folder_preview.set(None);
+                    process_files(source.clone(), selected);
+                }, move |_| folder_preview.set(None))}
+            }
+        
+            div {
+                class: "controls-row",
+                // Always show the upload button
+                label {
+                    r#for: "textreader",
+                    class: "file-upload-label",
+                    svg {
+                        class: "upload-icon",
+                        fill: "none",
+                        stroke: "currentColor",
+                        view_box: "0 0 24 24",
+                        xmlns: "http://www.w3.org/2000/svg",
+                        path {
+                            stroke_linecap: "round",
+                            stroke_linejoin: "round",
+                            stroke_width: "2",
+                            d: "M7 16a4 4 0 01-.88-7.903A5 5 0 1115.9 6L16 6a5 5 0 011 9.9M15 13l-3-3m0 0l-3 3m3-3v12"
+                        }
+                    }
+                    span { class: "upload-text", "Add Sequences" } 
                 }
-                button {
-                    class: "usercontrols",
-                    onclick: move |_| {
-                        copy_to_clipboard(files_uploaded.clone());
-                        show_popup.set(true);
-                        spawn(async move {
-                            my_sleep(3000).await;
-                            show_popup.set(false);
-                        });
-                    },
-                    "Copy to clipboard"
+                input {
+                    id: "textreader",
+                    class: "file-input-hidden",
+                    r#type: "file",
+                    accept: ".fastq,.fq,.fasta,.fa,.fna,.gz",
+                    multiple: true,
+                    onchange: upload_files
                 }
-                button {
-                    class: "usercontrols",
-                    onclick: move |_| {
-                        save_html(files_uploaded.clone(), numbers(), name_type_sig(), basesperbin(), spark_type());
-                    },
-                    "Save as HTML"
+                // desktop uses the native folder dialog, web the webkitdirectory input
+                if cfg!(target_arch = "wasm32") {
+                    label {
+                        r#for: "folderreader",
+                        class: "file-upload-label",
+                        span { class: "upload-text", "Add Folder" }
+                    }
+                } else {
+                    button {
+                        class: "file-upload-label",
+                        onclick: move |_evt| {
+                            #[cfg(not(target_arch = "wasm32"))]
+                            pick_folder(_evt);
+                        },
+                        span { class: "upload-text", "Add Folder" }
+                    }
                 }
-                select {
-                    r#name: "name_type_sig",
-                    class: "usercontrols",
-                    multiple: "false",
-                    oninput: move |ev| {
-                        name_type_sig.set(ev.value())
-                    },
-                    option {value: "basename", "Base filename"}
-                    option {value: "fullpath", "Full path"},
+                input {
+                    id: "folderreader",
+                    class: "file-input-hidden",
+                    r#type: "file",
+                    directory: true,
+                    multiple: true,
+                    onchange: upload_folder
                 }
-                div {
-                    class: "tooltip-container",
-                    div {
-                        class: "tooltip",
-                        "Choose number format", 
+
+                // Only show other controls if files are uploaded
+                if files_uploaded.len() > 0 {
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| {
+                            files_uploaded.write().clear();
+                            file_sources.write().clear();
+                            total_bases.set(0);
+                            total_reads.set(0);
+                            files_count_pre.set(0);
+                            files_count_post.set(0);
+                            name_type_sig.set("basename".to_string());
+                            progress_percentage.set(0.0);
+                            show_indexes.set(false);
+                            export_status.set(String::new());
+                            export_outputs.set(std::collections::BTreeSet::new());
+                            browser_index.set(None);
+                            search_results.set(Vec::new());
+                            screen_refs.set(Vec::new());
+                            saved_sketches.set(Vec::new());
+                            file_sketches.set(Vec::new());
+                            chart_zoom.set(None);
+                            selected_rows.set(std::collections::BTreeSet::new());
+                            show_compare.set(false);
+                            compare_zoom.set(None);
+                            detail_file.set(None);
+                            detail_zoom.set(None);
+                            species_results.set(Vec::new());
+                            species_status.set(String::new());
+                            sketch_status.set(String::new());
+                        },
+                        "Clear"
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| {
+                            copy_to_clipboard(files_uploaded.clone(), &total_row.read(), &screen_refs.read());
+                            show_popup.set(true);
+                            spawn(async move {
+                                my_sleep(3000).await;
+                                show_popup.set(false);
+                            });
+                        },
+                        "Copy to clipboard"
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| {
+                            save_html(files_uploaded.clone(), &total_row.read(), numbers(), name_type_sig(), &sparklines.read(), &screen_refs.read());
+                        },
+                        "Save as HTML"
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_filter.set(!show_filter()),
+                        if show_filter() { "Hide filter" } else { "Filter reads" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_subsample.set(!show_subsample()),
+                        if show_subsample() { "Hide subsample" } else { "Subsample" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_browser.set(!show_browser()),
+                        if show_browser() { "Hide reads" } else { "Browse reads" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_search.set(!show_search()),
+                        if show_search() { "Hide motif search" } else { "Motif search" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_screen.set(!show_screen()),
+                        if show_screen() { "Hide screen" } else { "Contamination screen" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        disabled: selected_rows.read().is_empty(),
+                        title: "Tick rows in the table to compare them",
+                        onclick: move |_| show_compare.set(!show_compare()),
+                        if show_compare() { "Hide comparison" } else { "Compare selected ({selected_rows.read().len()})" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_charts.set(!show_charts()),
+                        if show_charts() { "Hide plots" } else { "Plots" }
+                    }
+                    button {
+                        class: "usercontrols",
+                        onclick: move |_| show_similarity.set(!show_similarity()),
+                        if show_similarity() { "Hide similarity" } else { "Sample similarity" }
+                    }
+                    if files_uploaded.read().iter().any(|f| !f.index_counts.is_empty()) {
+                        button {
+                            class: "usercontrols",
+                            onclick: move |_| show_indexes.set(!show_indexes()),
+                            if show_indexes() { "Hide index analysis" } else { "Index analysis" }
+                        }
                     }
                     select {
-                        r#name: "numbers", id: "numbers",
+                        r#name: "name_type_sig",
                         class: "usercontrols",
-                        multiple: false,
+                        multiple: "false",
                         oninput: move |ev| {
-                            numbers.set(ev.value())
+                            name_type_sig.set(ev.value())
                         },
-                        option {value: "none", "Plain numeric"},
-                        option {value: "comma", "Thousands sep"},
-                        option {value: "human", "SI suffix"}
+                        option {value: "basename", "Base filename"}
+                        option {value: "fullpath", "Full path"},
                     }
-                }
-                select {  
-                    r#name: "spark_type", id: "spark_type",
-                    class: "usercontrols",
-                    multiple: "false",
-                    oninput: move |ev| {
-                        spark_type.set(ev.value())
-                    },
-                    option {value: "reads", "Plot reads"},
-                    option {value: "bases", "Plot bases"},
-                    option {value: "cumulative", "Plot base yield"}
-                }
-                div {
-                    class: "tooltip-container",
                     div {
-                        class: "tooltip",
-                        "Length histogram: bases per bin", 
-                        br {}, 
-                        "Showing {basesperbin().human_count_bare()}-{(basesperbin * 30).human_count_bare()}", " bp",
+                        class: "tooltip-container",
+                        div {
+                            class: "tooltip",
+                            "Choose number format", 
+                        }
+                        select {
+                            r#name: "numbers", id: "numbers",
+                            class: "usercontrols",
+                            multiple: false,
+                            oninput: move |ev| {
+                                numbers.set(ev.value())
+                            },
+                            option {value: "none", "Plain numeric"},
+                            option {value: "comma", "Thousands sep"},
+                            option {value: "human", "SI suffix"}
+                        }
                     }
-                    input {
-                        r#type: "number",
-                        id: "basesperbin",
+                    select {
+                        r#name: "group_by", id: "group_by",
                         class: "usercontrols",
-                        value: "{basesperbin}",
-                        min: "50",
-                        max: "5000",
-                        step: "50",
+                        multiple: "false",
                         oninput: move |ev| {
-                            if let Ok(value) = ev.value().parse::<usize>() {
-                                if value > 0 {
-                                    basesperbin.set(value);
-                                }
-                            }
-                        }
+                            group_by.set(ev.value())
+                        },
+                        option {value: "none", "One row per file"},
+                        option {value: "pairs", "Pair R1/R2 files"},
+                        option {value: "folder", "Group by folder"},
+                        option {value: "barcode", "Group by barcode"},
+                        option {value: "regex", "Group by regex"},
                     }
-                }
-            }
-        }
-
-        if files_uploaded.len() > 0 {
-            div {
-                class: "table-scroll",
-                table {
-                    id: "resultstable",
-                    thead {
-                        tr {
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("name".to_string(), !current_sort))
-                                },
-                                "File ",
-                                {format_thead(sort_by, "name")}
-                            }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("reads".to_string(), !current_sort))
-                                },
-                                "Reads ",
-                                {format_thead(sort_by, "reads")}
-                            }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("bases".to_string(), !current_sort))
-                                },
-                                "Bases ",
-                                {format_thead(sort_by, "bases")}
-                            }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("nx".to_string(), !current_sort))
-                                },
-                                "N50 ",
-                                {format_thead(sort_by, "nx")}
-                            }
-                            if spark_type() == "bases" {
-                                th { class: "histogram-header", "Bases length histogram" }
-                            } else if spark_type() == "cumulative" {
-                                th { class: "histogram-header", "Base yield over length" }
-                            } else {
-                                th { class: "histogram-header", "Reads length histogram" }
+                    if group_by() == "regex" {
+                        div {
+                            class: "tooltip-container",
+                            div {
+                                class: "tooltip",
+                                "Files with the same match (or first capture group) are pooled",
                             }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("gc".to_string(), !current_sort))
-                                },
-                                "GC% ",
-                                {format_thead(sort_by, "gc")}
+                            input {
+                                r#type: "text",
+                                class: if groups::valid_pattern(&group_regex.read()) { "usercontrols text-input" } else { "usercontrols text-input invalid" },
+                                placeholder: "e.g. (sample\\d+)",
+                                value: "{group_regex}",
+                                oninput: move |ev| group_regex.set(ev.value()),
                             }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("q30".to_string(), !current_sort))
-                                },
-                                "Q30% ",
-                                {format_thead(sort_by, "q30")}
+                        }
+                    }
+                    select {  
+                        r#name: "spark_type", id: "spark_type",
+                        class: "usercontrols",
+                        multiple: "false",
+                        oninput: move |ev| {
+                            spark_type.set(ev.value())
+                        },
+                        option {value: "reads", "Plot reads"},
+                        option {value: "bases", "Plot bases"},
+                        option {value: "cumulative", "Plot base yield"}
+                    }
+                    select {
+                        class: "usercontrols",
+                        value: "{y_mode}",
+                        oninput: move |ev| y_mode.set(ev.value()),
+                        option { value: "own", "Y-axis: per file" }
+                        option { value: "shared", "Y-axis: shared" }
+                        option { value: "percent", "Y-axis: % of file" }
+                    }
+                    select {
+                        class: "usercontrols",
+                        value: "{length_scale}",
+                        oninput: move |ev| length_scale.set(ev.value()),
+                        option { value: "auto", "Length bins: auto" }
+                        option { value: "log", "Length bins: log" }
+                        option { value: "fixed", "Length bins: fixed" }
+                    }
+                    div {
+                        class: "tooltip-container",
+                        div {
+                            class: "tooltip",
+                            "Length histogram: number of bins",
+                            br {},
+                            "Showing {length_bins.read().describe()}",
+                        }
+                        input {
+                            r#type: "number",
+                            id: "bincount",
+                            class: "usercontrols",
+                            value: "{bin_count}",
+                            min: "5",
+                            max: "100",
+                            oninput: move |ev| {
+                                if let Ok(value) = ev.value().parse::<usize>() {
+                                    if (5..=100).contains(&value) {
+                                        bin_count.set(value);
+                                    }
+                                }
                             }
-                            th {
-                                class: "sortable-header",
-                                onclick: {
-                                    let current_sort = sort_by.read().1;
-                                    move |_| sort_by.set(("m_qscore".to_string(), !current_sort))
-                                },
-                                "Reads median Q",
-                                {format_thead(sort_by, "m_qscore")}
+                        }
+                    }
+                    div {
+                        class: "tooltip-container",
+                        hidden: length_scale() != "fixed",
+                        div {
+                            class: "tooltip",
+                            "Length histogram: bases per bin",
+                            br {},
+                            "Showing {length_bins.read().describe()}",
+                        }
+                        input {
+                            r#type: "number",
+                            id: "basesperbin",
+                            class: "usercontrols",
+                            value: "{basesperbin}",
+                            min: "50",
+                            max: "5000",
+                            step: "50",
+                            oninput: move |ev| {
+                                if let Ok(value) = ev.value().parse::<usize>() {
+                                    if value > 0 {
+                                        basesperbin.set(value);
+                                    }
+                                }
                             }
-                            if spark_type() == "bases" {
-                                th { class: "histogram-header", "Bases Qscore histogram" }
-                            } else if spark_type() == "cumulative" {
-                                th { class: "histogram-header", "Base yield over Qscore" }
-                            } else {
-                                th { class: "histogram-header", "Reads Qscore histogram" }
+                        }
+                    }
+                    select {
+                        class: "usercontrols",
+                        value: "{q_scale}",
+                        oninput: move |ev| q_scale.set(ev.value()),
+                        option { value: "auto", "Q bins: auto" }
+                        option { value: "fixed", "Q bins: fixed" }
+                    }
+                    div {
+                        class: "tooltip-container",
+                        hidden: q_scale() != "fixed",
+                        div {
+                            class: "tooltip",
+                            "Qscore histogram: highest Q, higher values go into the last bin",
+                            br {},
+                            "Showing {quality_bins.read().describe()}",
+                        }
+                        input {
+                            r#type: "number",
+                            id: "qmax",
+                            class: "usercontrols",
+                            value: "{q_max}",
+                            min: "10",
+                            max: "100",
+                            oninput: move |ev| {
+                                if let Ok(value) = ev.value().parse::<usize>() {
+                                    if (10..=100).contains(&value) {
+                                        q_max.set(value);
+                                    }
+                                }
                             }
                         }
                     }
-                    tbody {
-                        {maketable(files_uploaded, name_type_sig(), numbers(), basesperbin, spark_type, total_reads, total_bases, sort_by)}
+                    div {
+                        class: "tooltip-container",
+                        hidden: q_scale() != "fixed",
+                        div {
+                            class: "tooltip",
+                            "Qscore histogram: Q units per bin",
+                            br {},
+                            "Showing {quality_bins.read().describe()}",
+                        }
+                        input {
+                            r#type: "number",
+                            id: "qwidth",
+                            class: "usercontrols",
+                            value: "{q_width}",
+                            min: "1",
+                            max: "10",
+                            oninput: move |ev| {
+                                if let Ok(value) = ev.value().parse::<usize>() {
+                                    if (1..=10).contains(&value) {
+                                        q_width.set(value);
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
             }
-        }
 
-        footer {
-            class: "app-footer",
-            div {
-                class: "footer-left",
-                {format!("fasterX v{} © 2025 | ", env!("CARGO_PKG_VERSION"))}
-                a {
-                    href: "#",
-                    style: "text-decoration: none; color: inherit; cursor: pointer;",
-                    onclick: move |_| {
-                        #[cfg(not(target_arch = "wasm32"))]
-                        let _ = open::that("https://github.com/angelovangel/faster-app");
-                        #[cfg(target_arch = "wasm32")]
-                        {
-                            let window = web_sys::window().expect("window not found");
-                            let _ = window.open_with_url_and_target("https://github.com/angelovangel/faster-app", "_blank");
+            if show_filter() && files_uploaded.len() > 0 {
+                {components::filter_panel(read_filter, export_status, move |_| export_reads(exports::ReadSelector::Filter(read_filter())))}
+            }
+            if show_browser() && files_uploaded.len() > 0 {
+                {components::read_browser(file_sources.read().keys().cloned().collect(), browser_file, browser_index, browser_records.read().clone().unwrap_or_default(), browser_loading(), browser_page, browser_limits, open_in_browser)}
+            }
+            if show_compare() && !compared.read().is_empty() {
+                {components::compare_panel(compared, selected_rows, compare_normalise, compare_zoom, compare_brush, compare_readout, length_bins(), spark_type())}
+            }
+            if show_charts() && files_uploaded.len() > 0 {
+                {components::chart_panel(files_uploaded, chart_file, chart_zoom, chart_brush, chart_readout, length_bins(), spark_type())}
+            }
+            if show_similarity() && files_uploaded.len() > 0 {
+                {components::similarity_panel(similarity, similarity_threshold, saved_sketches, sketch_status, run_sketches, import_sketches, export_sketches)}
+            }
+            if show_screen() && files_uploaded.len() > 0 {
+                {components::screen_panel(references, add_references, run_screen)}
+                {components::species_panel(species_db, species_results, species_status, add_db_files, add_db_folder, run_species)}
+            }
+            if show_search() && files_uploaded.len() > 0 {
+                {components::search_panel(search_motifs, search_mismatches, search_results, run_search)}
+            }
+            if show_subsample() && files_uploaded.len() > 0 {
+                {components::subsample_panel(subsample_settings, export_status, move |_| export_reads(exports::ReadSelector::Subsample(subsample_settings())))}
+            }
+
+            if files_uploaded.len() > 0 && table_rows.read().iter().any(|name| Some(name) == detail_file.read().as_ref()) {
+                {components::detail_panel(table_rows, files_uploaded, file_groups, detail_file, detail_zoom, detail_brush, detail_readout, length_bins(), spark_type(), samplesheet, &screen_refs.read(), export_detail)}
+            } else if files_uploaded.len() > 0 {
+                div {
+                    class: "table-scroll",
+                    table {
+                        id: "resultstable",
+                        thead {
+                            tr {
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("name".to_string(), !current_sort))
+                                    },
+                                    "File ",
+                                    {format_thead(sort_by, "name")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("platform".to_string(), !current_sort))
+                                    },
+                                    "Platform ",
+                                    {format_thead(sort_by, "platform")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("reads".to_string(), !current_sort))
+                                    },
+                                    "Reads ",
+                                    {format_thead(sort_by, "reads")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("bases".to_string(), !current_sort))
+                                    },
+                                    "Bases ",
+                                    {format_thead(sort_by, "bases")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("nx".to_string(), !current_sort))
+                                    },
+                                    "N50 ",
+                                    {format_thead(sort_by, "nx")}
+                                }
+                                if spark_type() == "bases" {
+                                    th { class: "histogram-header", "Bases length histogram" }
+                                } else if spark_type() == "cumulative" {
+                                    th { class: "histogram-header", "Base yield over length" }
+                                } else {
+                                    th { class: "histogram-header", "Reads length histogram" }
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("gc".to_string(), !current_sort))
+                                    },
+                                    "GC% ",
+                                    {format_thead(sort_by, "gc")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("q30".to_string(), !current_sort))
+                                    },
+                                    "Q30% ",
+                                    {format_thead(sort_by, "q30")}
+                                }
+                                th {
+                                    class: "sortable-header",
+                                    onclick: {
+                                        let current_sort = sort_by.read().1;
+                                        move |_| sort_by.set(("m_qscore".to_string(), !current_sort))
+                                    },
+                                    "Reads median Q",
+                                    {format_thead(sort_by, "m_qscore")}
+                                }
+                                if spark_type() == "bases" {
+                                    th { class: "histogram-header", title: "Dashed line: mean read Q, solid line: median read Q", "Bases Qscore histogram" }
+                                } else if spark_type() == "cumulative" {
+                                    th { class: "histogram-header", title: "Dashed line: mean read Q, solid line: median read Q", "Base yield over Qscore" }
+                                } else {
+                                    th { class: "histogram-header", title: "Dashed line: mean read Q, solid line: median read Q", "Reads Qscore histogram" }
+                                }
+                                if files_uploaded.read().iter().any(|f| f.platform == "PacBio") {
+                                    th {
+                                        class: "sortable-header",
+                                        onclick: {
+                                            let current_sort = sort_by.read().1;
+                                            move |_| sort_by.set(("passes".to_string(), !current_sort))
+                                        },
+                                        "Mean passes ",
+                                        {format_thead(sort_by, "passes")}
+                                    }
+                                    th { "rq Q20%" }
+                                    th { "rq Q30%" }
+                                }
+                                if file_groups.read().iter().any(|g| !g.inserts.is_empty()) {
+                                    th { "Insert median" }
+                                    th { class: "histogram-header", "Insert size histogram" }
+                                }
+                                for name in screen_refs.read().iter() {
+                                    th { "{name} %" }
+                                }
+                            }
                         }
-                    },
-                    dangerous_inner_html: r#"<svg height="18" width="18" viewBox="0 0 16 16" fill="currentColor" style="vertical-align:middle; margin-right:4px;"><path d="M8 0C3.58 0 0 3.58 0 8c0 3.54 2.29 6.53 5.47 7.59.4.07.55-.17.55-.38 0-.19-.01-.82-.01-1.49-2.01.37-2.53-.49-2.69-.94-.09-.23-.48-.94-.82-1.13-.28-.15-.68-.52-.01-.53.63-.01 1.08.58 1.23.82.72 1.21 1.87.87 2.33.66.07-.52.28-.87.51-1.07-1.78-.2-3.64-.89-3.64-3.95 0-.87.31-1.59.82-2.15-.08-.2-.36-1.01.08-2.12 0 0 .67-.21 2.2.82a7.65 7.65 0 0 1 2-.27c.68 0 1.36.09 2 .27 1.53-1.04 2.2-.82 2.2-.82.44 1.11.16 1.92.08 2.12.51.56.82 1.27.82 2.15 0 3.07-1.87 3.75-3.65 3.95.29.25.54.73.54 1.48 0 1.07-.01 1.93-.01 2.19 0 .21.15.46.55.38A8.013 8.013 0 0 0 16 8c0-4.42-3.58-8-8-8z"/></svg>"#,
+                        tbody {
+                            {maketable(files_uploaded, file_groups, name_type_sig(), numbers(), sparklines, total_row, sort_by, expanded_groups, &screen_refs.read(), selected_rows, detail_file)}
+                        }
+                    }
+                    if show_indexes() {
+                        {components::index_panel(files_uploaded, samplesheet)}
+                    }
                 }
             }
-            if busy() || !myduration().is_empty() {
+
+            footer {
+                class: "app-footer",
                 div {
-                    class: "footer-right",
-                    style: "display: flex; gap: 15px; font-size: 0.9em; opacity: 0.9;",
-                    span { "Time: {myduration}" }
-                    if !memory_usage().is_empty() {
-                        span { " | Mem: {memory_usage}" }
+                    class: "footer-left",
+                    {format!("fasterX v{} © 2025 | ", env!("CARGO_PKG_VERSION"))}
+                    a {
+                        href: "#",
+                        style: "text-decoration: none; color: inherit; cursor: pointer;",
+                        onclick: move |_| {
+                            #[cfg(not(target_arch = "wasm32"))]
+                            let _ = open::that("https://github.com/angelovangel/faster-app");
+                            #[cfg(target_arch = "wasm32")]
+                            {
+                                let window = web_sys::window().expect("window not found");
+                                let _ = window.open_with_url_and_target("https://github.com/angelovangel/faster-app", "_blank");
+                            }
+                        },
+                        dangerous_inner_html: r#"<svg height="18" width="18" viewBox="0 0 16 16" fill="currentColor" style="vertical-align:middle; margin-right:4px;"><path d="M8 0C3.58 0 0 3.58 0 8c0 3.54 2.29 6.53 5.47 7.59.4.07.55-.17.55-.38 0-.19-.01-.82-.01-1.49-2.01.37-2.53-.49-2.69-.94-.09-.23-.48-.94-.82-1.13-.28-.15-.68-.52-.01-.53.63-.01 1.08.58 1.23.82.72 1.21 1.87.87 2.33.66.07-.52.28-.87.51-1.07-1.78-.2-3.64-.89-3.64-3.95 0-.87.31-1.59.82-2.15-.08-.2-.36-1.01.08-2.12 0 0 .67-.21 2.2.82a7.65 7.65 0 0 1 2-.27c.68 0 1.36.09 2 .27 1.53-1.04 2.2-.82 2.2-.82.44 1.11.16 1.92.08 2.12.51.56.82 1.27.82 2.15 0 3.07-1.87 3.75-3.65 3.95.29.25.54.73.54 1.48 0 1.07-.01 1.93-.01 2.19 0 .21.15.46.55.38A8.013 8.013 0 0 0 16 8c0-4.42-3.58-8-8-8z"/></svg>"#,
+                    }
+                }
+                if busy() || !myduration().is_empty() {
+                    div {
+                        class: "footer-right",
+                        style: "display: flex; gap: 15px; font-size: 0.9em; opacity: 0.9;",
+                        span { "Time: {myduration}" }
+                        if !memory_usage().is_empty() {
+                            span { " | Mem: {memory_usage}" }
+                        }
                     }
                 }
             }
-        }
 
-        if *busy.read() {
-            div {
-                class: "popup",
-                "Please wait... {files_count_post} of {files_count_pre} files processed ({HumanCount(total_reads())} reads)",
+            if *busy.read() {
                 div {
-                    class: "progress-bar-container",
+                    class: "popup",
+                    "Please wait... {files_count_post} of {files_count_pre} files processed ({HumanCount(total_reads())} reads)",
                     div {
-                        class: "progress-bar-area",
+                        class: "progress-bar-container",
                         div {
-                            class: "progress-bar",
-                            style: "width: {progress_percentage()}%;",
+                            class: "progress-bar-area",
+                            div {
+                                class: "progress-bar",
+                                style: "width: {progress_percentage()}%;",
+                            }
+                        }
+                        button {
+                            class: "progress-cancel-btn",
+                            onclick: move |_| {
+                                cancel_processing.set(true);
+                            },
+                            "Cancel ✕"
                         }
-                    }
-                    button {
-                        class: "progress-cancel-btn",
-                        onclick: move |_| {
-                            cancel_processing.set(true);
-                        },
-                        "Cancel ✕"
                     }
                 }
             }
-        }
 
-        if *ready.read() {
-            div {
-                class: "popup",
-                "Finished processing {files_count_post} files in {myduration}!"
+            if *ready.read() {
+                div {
+                    class: "popup",
+                    "Finished processing {files_count_post} files in {myduration}!"
+                }
             }
-        }
 
-        if *show_popup.read() {
-            div {
-                class: "popup",
-                "{files_uploaded.len()} entries copied to clipboard!"
+            if *show_popup.read() {
+                div {
+                    class: "popup",
+                    "{files_uploaded.len()} entries copied to clipboard!"
+                }
             }
         }
     }
@@ -0,0 +1,143 @@
+// Typed values of the table columns; the unit decides how a value is shown, exported and sorted,
+// and a value that cannot be computed (an empty file, reads without qualities) shows as "-"
+
+use std::cmp::Ordering;
+
+use human_repr::HumanCount as _;
+use indicatif::HumanCount;
+
+use crate::UploadedFile;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Unit {
+    Count,   // reads, bases and lengths, shown as set by the numbers selector
+    Percent,
+    Qscore,
+    Number, // other decimals, e.g. mean passes
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Metric {
+    pub value: Option<f64>,
+    pub unit: Unit,
+    pub precision: usize, // decimals on display; exports keep the full value
+}
+
+impl Metric {
+    pub fn count(n: u64) -> Self {
+        Metric { value: Some(n as f64), unit: Unit::Count, precision: 0 }
+    }
+
+    // part of total in percent, none of an empty total
+    pub fn percent(part: f64, total: f64) -> Self {
+        Self::from_percent((total > 0.0).then(|| part / total * 100.0))
+    }
+
+    pub fn from_percent(value: Option<f64>) -> Self {
+        Metric { value, unit: Unit::Percent, precision: 2 }
+    }
+
+    pub fn qscore(value: Option<f64>, precision: usize) -> Self {
+        Metric { value, unit: Unit::Qscore, precision }
+    }
+
+    pub fn number(value: Option<f64>) -> Self {
+        Metric { value, unit: Unit::Number, precision: 2 }
+    }
+
+    // numbers_type is "comma", "human" or "none" and only changes counts
+    pub fn display(&self, numbers_type: &str) -> String {
+        match (self.value, self.unit) {
+            (None, _) => "-".to_string(),
+            (Some(v), Unit::Count) => match numbers_type {
+                "comma" => HumanCount(v as u64).to_string(),
+                "human" => (v as u64).human_count_bare().to_string(),
+                _ => format!("{}", v as u64),
+            },
+            (Some(v), _) => format!("{:.*}", self.precision, v),
+        }
+    }
+
+    // unrounded, for the clipboard and the command line
+    pub fn export(&self) -> String {
+        match (self.value, self.unit) {
+            (None, _) => "-".to_string(),
+            (Some(v), Unit::Count) => format!("{}", v as u64),
+            (Some(v), _) => format!("{}", v),
+        }
+    }
+
+    // missing values sort below all others
+    pub fn order(&self, other: &Metric) -> Ordering {
+        match (self.value, other.value) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+// a numeric column of the table by its sort key; unknown keys give a missing value
+pub fn column(f: &UploadedFile, key: &str) -> Metric {
+    match key {
+        "reads" => Metric::count(f.reads),
+        "bases" => Metric::count(f.bases),
+        "nx" => Metric::count(f.nx),
+        "gc" => f.gc,
+        "q20" => f.q20,
+        "q30" => f.q30,
+        "m_qscore" => Metric::qscore((!f.q_hash.is_empty()).then_some(f.m_qscore as f64), 0),
+        "passes" => Metric::number(f.hifi.mean_passes()),
+        "rq_q20" => Metric::from_percent(f.hifi.rq_q20_percent()),
+        "rq_q30" => Metric::from_percent(f.hifi.rq_q30_percent()),
+        _ => Metric::number(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_is_numeric_with_missing_values_below() {
+        let (low, high) = (Metric::from_percent(Some(9.5)), Metric::from_percent(Some(10.0)));
+        assert_eq!(low.order(&high), Ordering::Less);
+        assert_eq!(Metric::count(10).order(&Metric::count(9)), Ordering::Greater);
+
+        let missing = Metric::from_percent(None);
+        assert_eq!(missing.order(&Metric::from_percent(Some(0.0))), Ordering::Less);
+        assert_eq!(high.order(&missing), Ordering::Greater);
+        assert_eq!(missing.order(&missing), Ordering::Equal);
+
+        let mut column = vec![high, missing, low];
+        column.sort_by(|a, b| a.order(b));
+        assert_eq!(column.iter().map(|m| m.value).collect::<Vec<_>>(), vec![None, Some(9.5), Some(10.0)]);
+    }
+
+    #[test]
+    fn missing_values_show_as_dash() {
+        for m in [Metric { value: None, ..Metric::count(0) }, Metric::from_percent(None), Metric::qscore(None, 1), Metric::number(None)] {
+            assert_eq!(m.display("comma"), "-");
+            assert_eq!(m.export(), "-");
+        }
+    }
+
+    #[test]
+    fn display_rounds_and_export_keeps_full_values() {
+        let count = Metric::count(1_234_567);
+        assert_eq!(count.display("comma"), "1,234,567");
+        assert_eq!(count.display("none"), "1234567");
+        assert_eq!(count.export(), "1234567");
+
+        let gc = Metric::percent(1.0, 3.0);
+        assert_eq!(gc.display("comma"), "33.33");
+        assert!(gc.export().starts_with("33.3333"));
+        assert_eq!(Metric::qscore(Some(17.25), 1).display("none"), "17.2");
+    }
+
+    #[test]
+    fn percent_of_empty_total_is_missing() {
+        assert_eq!(Metric::percent(0.0, 0.0).value, None);
+        assert_eq!(Metric::percent(5.0, 0.0).value, None);
+        assert_eq!(Metric::percent(1.0, 4.0).value, Some(25.0));
+    }
+}
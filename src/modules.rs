@@ -77,3 +77,63 @@ pub fn median(numbers: &mut Vec<u8>) -> u8 {
     }
 }
 
+
+// reverse complement, IUPAC aware; anything unknown becomes N
+pub fn revcomp(seq: &[u8]) -> Vec<u8> {
+    seq.iter()
+        .rev()
+        .map(|&b| match b {
+            b'A' => b'T', b'T' => b'A', b'G' => b'C', b'C' => b'G',
+            b'a' => b't', b't' => b'a', b'g' => b'c', b'c' => b'g',
+            b'R' => b'Y', b'Y' => b'R', b'K' => b'M', b'M' => b'K',
+            b'B' => b'V', b'V' => b'B', b'D' => b'H', b'H' => b'D',
+            b'S' => b'S', b'W' => b'W',
+            _ => b'N',
+        })
+        .collect()
+}
+
+// insert size of a read pair from the overlap between R1 and the reverse complement of R2
+// overlaps of at least min_overlap bp are tried longest first (also inserts shorter than the reads),
+// the first one with at most 10% mismatches wins, so a short chance match cannot beat a long true one
+pub fn insert_size(r1: &[u8], r2: &[u8], min_overlap: usize) -> Option<usize> {
+    let rc2 = revcomp(r2);
+    let (len1, len2) = (r1.len(), rc2.len());
+    // in fragment coordinates R1 spans [0, len1), R2 spans [insert - len2, insert)
+    let span = |insert: usize| ((insert as i64 - len2 as i64).max(0) as usize, insert.min(len1));
+    let (short, long) = (len1.min(len2), len1.max(len2));
+    let min_overlap = min_overlap.max(1);
+    if short < min_overlap {
+        return None;
+    }
+    // longest overlaps first: every insert from short to long overlaps the whole shorter
+    // read, each shorter overlap comes from one insert on either side
+    let mut inserts = (short..=long).chain((min_overlap..short).rev().flat_map(|overlap| [overlap, len1 + len2 - overlap]));
+
+    inserts.find(|&insert| {
+        let (start, end) = span(insert);
+        let r2_start = insert as i64 - len2 as i64;
+        let max_mismatches = (end - start) / 10;
+        let mut mismatches = 0;
+        for pos in start..end {
+            if !r1[pos].eq_ignore_ascii_case(&rc2[(pos as i64 - r2_start) as usize]) {
+                mismatches += 1;
+                if mismatches > max_mismatches {
+                    return false;
+                }
+            }
+        }
+        true
+    })
+}
+
+pub fn median_i64(numbers: &[i64]) -> i64 {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    }
+}
@@ -0,0 +1,123 @@
+// Mean quality and base composition along the reads; single positions up to 50, then
+// bins of 10, 100, 1k and 10k bp so long reads stay small
+
+pub const BASES: [&str; 5] = ["A", "C", "G", "T", "N"];
+
+#[derive(Clone, Default, PartialEq)]
+pub struct PositionBin {
+    pub qual_sum: u64,
+    pub qual_count: u64,
+    pub bases: [u64; 5], // A, C, G, T and anything else
+}
+
+// bins grow with the longest read seen
+#[derive(Clone, Default, PartialEq)]
+pub struct Positions {
+    pub bins: Vec<PositionBin>,
+}
+
+impl Positions {
+    // first read position (0-based) of a bin
+    pub fn start(bin: usize) -> usize {
+        if bin < 50 {
+            return bin;
+        }
+        let stage = ((bin - 50) / 45).min(3);
+        let step = 10usize.pow(stage as u32 + 1);
+        5 * step + (bin - 50 - 45 * stage) * step
+    }
+
+    pub fn end(bin: usize) -> usize {
+        Self::start(bin + 1)
+    }
+
+    pub fn add(&mut self, seq: &[u8], qual: &[u8]) {
+        let mut bin = 0;
+        while Self::start(bin) < seq.len() {
+            if bin == self.bins.len() {
+                self.bins.push(PositionBin::default());
+            }
+            let (start, end) = (Self::start(bin), Self::end(bin).min(seq.len()));
+            let counts = &mut self.bins[bin];
+            for &b in &seq[start..end] {
+                let base = match b {
+                    b'A' | b'a' => 0,
+                    b'C' | b'c' => 1,
+                    b'G' | b'g' => 2,
+                    b'T' | b't' => 3,
+                    _ => 4,
+                };
+                counts.bases[base] += 1;
+            }
+            if qual.len() >= end {
+                counts.qual_sum += qual[start..end].iter().map(|&q| q.saturating_sub(33) as u64).sum::<u64>();
+                counts.qual_count += (end - start) as u64;
+            }
+            bin += 1;
+        }
+    }
+
+    pub fn merge(&mut self, other: &Positions) {
+        if self.bins.len() < other.bins.len() {
+            self.bins.resize(other.bins.len(), PositionBin::default());
+        }
+        for (a, b) in self.bins.iter_mut().zip(other.bins.iter()) {
+            a.qual_sum += b.qual_sum;
+            a.qual_count += b.qual_count;
+            for (x, y) in a.bases.iter_mut().zip(b.bases.iter()) {
+                *x += y;
+            }
+        }
+    }
+
+    pub fn mean_quality(&self, bin: usize) -> Option<f64> {
+        let b = &self.bins[bin];
+        (b.qual_count > 0).then(|| b.qual_sum as f64 / b.qual_count as f64)
+    }
+
+    // share of one of BASES at the positions of a bin, in percent
+    pub fn percent(&self, bin: usize, base: usize) -> f64 {
+        let counts = &self.bins[bin].bases;
+        let total: u64 = counts.iter().sum();
+        if total == 0 { 0.0 } else { counts[base] as f64 / total as f64 * 100.0 }
+    }
+
+    // 1-based read positions of a bin, as in the plots
+    pub fn label(bin: usize) -> String {
+        let (start, end) = (Self::start(bin) + 1, Self::end(bin));
+        if start == end { format!("{}", start) } else { format!("{}-{}", start, end) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bin_boundaries() {
+        assert_eq!((Positions::start(49), Positions::end(49)), (49, 50));
+        assert_eq!((Positions::start(50), Positions::end(50)), (50, 60));
+        assert_eq!((Positions::start(94), Positions::end(94)), (490, 500));
+        assert_eq!((Positions::start(95), Positions::end(95)), (500, 600));
+        assert_eq!((Positions::start(184), Positions::end(184)), (49_000, 50_000));
+        assert_eq!((Positions::start(185), Positions::end(185)), (50_000, 60_000));
+        assert_eq!(Positions::label(49), "50");
+        assert_eq!(Positions::label(95), "501-600");
+    }
+
+    #[test]
+    fn add_read_ending_mid_bin() {
+        let mut positions = Positions::default();
+        positions.add(&[b'A'; 55], &[b'I'; 55]); // Q40
+        assert_eq!(positions.bins.len(), 51);
+        assert_eq!(positions.bins[50].bases[0], 5);
+        assert_eq!(positions.bins[50].qual_count, 5);
+        assert_eq!(positions.mean_quality(50), Some(40.0));
+
+        positions.add(&[b'C'; 505], &[]);
+        assert_eq!(positions.bins.len(), 96);
+        assert_eq!(positions.bins[95].bases[1], 5);
+        assert_eq!(positions.bins[95].qual_count, 0);
+        assert_eq!(positions.bins[50].bases[1], 10); // the longer read covers the whole bin
+    }
+}
@@ -0,0 +1,181 @@
+// Random access to the records of one file, for the read browser; the index keeps the file as
+// read, gzipped or not, and where each record starts in the decompressed stream
+
+use std::io::{BufRead, BufReader, Read};
+
+use bio::io::fastq;
+use flate2::read::MultiGzDecoder;
+
+use crate::{charts, modules};
+
+pub const PAGE_SIZE: usize = 20;
+
+pub struct ReadIndex {
+    raw: Vec<u8>, // file bytes as read
+    gz: bool,
+    offsets: Vec<u64>, // start of each record in the decompressed stream
+    lengths: Vec<u32>,
+    qscores: Vec<u8>, // mean read Q
+    fasta: bool,
+}
+
+// records one at a time from a stream, tracking the position in the decompressed bytes
+struct RecordStream<R: BufRead> {
+    reader: R,
+    pos: u64,
+    fasta: bool,
+}
+
+impl<R: BufRead> RecordStream<R> {
+    // next line without its line end, false at the end of the stream
+    fn line(&mut self, buf: &mut Vec<u8>) -> bool {
+        buf.clear();
+        match self.reader.read_until(b'\n', buf) {
+            Ok(0) | Err(_) => false,
+            Ok(n) => {
+                self.pos += n as u64;
+                while matches!(buf.last(), Some(b'\n' | b'\r')) {
+                    buf.pop();
+                }
+                true
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.reader.fill_buf().ok()?.first().copied()
+    }
+
+    // the next record and where it starts
+    fn next_record(&mut self) -> Option<(u64, fastq::Record)> {
+        let start = self.pos;
+        let mut header = Vec::new();
+        if !self.line(&mut header) {
+            return None;
+        }
+        let marker = if self.fasta { b'>' } else { b'@' };
+        if header.first() != Some(&marker) {
+            return None;
+        }
+        let header = String::from_utf8_lossy(&header[1..]).to_string();
+        let (id, desc) = match header.split_once(char::is_whitespace) {
+            Some((id, desc)) => (id.to_string(), Some(desc.to_string())),
+            None => (header.clone(), None),
+        };
+
+        let mut seq = Vec::new();
+        let mut qual = Vec::new();
+        let mut line = Vec::new();
+        if self.fasta {
+            // sequence may span several lines
+            while self.peek().is_some_and(|b| b != b'>') {
+                self.line(&mut line);
+                seq.extend_from_slice(&line);
+            }
+        } else {
+            self.line(&mut seq);
+            self.line(&mut line); // '+' line
+            self.line(&mut qual);
+        }
+        Some((start, fastq::Record::with_attrs(&id, desc.as_deref(), &seq, &qual)))
+    }
+}
+
+impl ReadIndex {
+    // one streaming pass records where each record starts, so that a page of reads can be
+    // parsed later without keeping a decompressed copy of the file
+    pub fn build(raw: Vec<u8>, gz: bool, fasta: bool) -> ReadIndex {
+        let mut index = ReadIndex { raw, gz, offsets: Vec::new(), lengths: Vec::new(), qscores: Vec::new(), fasta };
+        let (mut offsets, mut lengths, mut qscores) = (Vec::new(), Vec::new(), Vec::new());
+        {
+            let mut stream = index.stream_from(0);
+            while let Some((start, rec)) = stream.next_record() {
+                offsets.push(start);
+                lengths.push(rec.seq().len() as u32);
+                qscores.push(modules::qscore_mean(rec.qual()));
+            }
+        }
+        (index.offsets, index.lengths, index.qscores) = (offsets, lengths, qscores);
+        index
+    }
+
+    // records from a decompressed offset on; plain files start there directly,
+    // gzipped ones are decompressed from the beginning and skipped up to it
+    fn stream_from(&self, offset: u64) -> RecordStream<BufReader<Box<dyn Read + '_>>> {
+        let reader: Box<dyn Read + '_> = if self.gz {
+            let mut decoder = MultiGzDecoder::new(&self.raw[..]);
+            let _ = std::io::copy(&mut (&mut decoder).take(offset), &mut std::io::sink());
+            Box::new(decoder)
+        } else {
+            Box::new(&self.raw[(offset as usize).min(self.raw.len())..])
+        };
+        RecordStream { reader: BufReader::new(reader), pos: offset, fasta: self.fasta }
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    // indices of the records passing length and mean Q limits
+    pub fn matching(&self, min_length: u32, min_qscore: u8) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&i| self.lengths[i] >= min_length && self.qscores[i] >= min_qscore)
+            .collect()
+    }
+
+    // the records of one page of matching reads, in one pass over the stream
+    pub fn page(&self, limits: (u32, u8), page: usize) -> Vec<(usize, fastq::Record)> {
+        let matching = self.matching(limits.0, limits.1);
+        let pages = matching.len().div_ceil(PAGE_SIZE).max(1);
+        let wanted: Vec<usize> = matching.into_iter().skip(page.min(pages - 1) * PAGE_SIZE).take(PAGE_SIZE).collect();
+        let (Some(&first), Some(&last)) = (wanted.first(), wanted.last()) else {
+            return Vec::new();
+        };
+        let mut stream = self.stream_from(self.offsets[first]);
+        let mut records = Vec::new();
+        for i in first..=last {
+            let Some((_, rec)) = stream.next_record() else {
+                break;
+            };
+            if wanted.binary_search(&i).is_ok() {
+                records.push((i, rec));
+            }
+        }
+        records
+    }
+}
+
+// sequence as html, bases coloured by their quality; runs of the same colour share a span
+pub fn quality_coloured(seq: &[u8], qual: &[u8], max_bases: usize) -> String {
+    let class = |q: u8| match q.saturating_sub(33) {
+        0..=9 => "q-low",
+        10..=19 => "q-mid",
+        20..=29 => "q-good",
+        _ => "q-high",
+    };
+    let shown = seq.len().min(max_bases);
+    let mut html = String::new();
+    let mut current = "";
+    let mut run = String::new(); // bases of the current span, escaped as they are rendered as html
+    for (i, &base) in seq.iter().enumerate().take(shown) {
+        let c = qual.get(i).map(|&q| class(q)).unwrap_or("q-none");
+        if c != current {
+            if !current.is_empty() {
+                html.push_str(&charts::escape(&run));
+                html.push_str("</span>");
+                run.clear();
+            }
+            html.push_str(&format!("<span class=\"{}\">", c));
+            current = c;
+        }
+        run.push(base as char);
+    }
+    if !current.is_empty() {
+        html.push_str(&charts::escape(&run));
+        html.push_str("</span>");
+    }
+    if seq.len() > shown {
+        html.push_str(&format!(" … ({} more bases)", seq.len() - shown));
+    }
+    html
+}
@@ -0,0 +1,89 @@
+// Contamination screen of a read sample against local reference sequences (phiX, lambda, ...)
+
+use std::collections::HashMap;
+
+use crate::kmers;
+
+const K: usize = 15;
+const W: usize = 10;
+const MIN_HITS: usize = 3; // shared minimizers for a read to count as a hit
+pub const SCREEN_SAMPLE: usize = 10_000; // reads screened per file
+pub const SCREEN_SEED: u64 = 42; // the same reads are screened on every run
+const SHARED: u16 = u16::MAX; // minimizer present in more than one reference
+
+#[derive(Clone, Default)]
+pub struct ReferenceIndex {
+    pub names: Vec<String>,
+    minimizers: HashMap<u64, u16>,
+}
+
+// hits per reference among the screened reads of one file
+#[derive(Clone, Default, PartialEq)]
+pub struct ScreenCounts {
+    pub screened: u64,
+    pub hits: Vec<u64>,
+}
+
+impl ScreenCounts {
+    pub fn percent(&self, reference: usize) -> Option<f64> {
+        let hits = self.hits.get(reference)?;
+        (self.screened > 0).then(|| *hits as f64 / self.screened as f64 * 100.0)
+    }
+
+    pub fn merge(&mut self, other: &ScreenCounts) {
+        if other.hits.is_empty() {
+            return;
+        }
+        if self.hits.is_empty() {
+            self.hits = vec![0; other.hits.len()];
+        }
+        self.screened += other.screened;
+        for (a, b) in self.hits.iter_mut().zip(other.hits.iter()) {
+            *a += b;
+        }
+    }
+}
+
+impl ReferenceIndex {
+    pub fn add(&mut self, name: &str, seqs: &[Vec<u8>]) {
+        let id = self.names.len() as u16;
+        self.names.push(name.to_string());
+        for seq in seqs {
+            for m in kmers::minimizers(seq, K, W) {
+                self.minimizers
+                    .entry(m)
+                    .and_modify(|r| if *r != id { *r = SHARED })
+                    .or_insert(id);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // reference the read belongs to: the one sharing most minimizers, if at least MIN_HITS
+    pub fn classify(&self, seq: &[u8]) -> Option<usize> {
+        let mut counts = vec![0usize; self.names.len()];
+        for m in kmers::minimizers(seq, K, W) {
+            if let Some(&r) = self.minimizers.get(&m) {
+                if r != SHARED {
+                    counts[r as usize] += 1;
+                }
+            }
+        }
+        let (best, &n) = counts.iter().enumerate().max_by_key(|(_, &n)| n)?;
+        (n >= MIN_HITS).then_some(best)
+    }
+
+    pub fn new_counts(&self) -> ScreenCounts {
+        ScreenCounts { screened: 0, hits: vec![0; self.names.len()] }
+    }
+
+    pub fn screen(&self, counts: &mut ScreenCounts, seq: &[u8]) {
+        counts.screened += 1;
+        if let Some(r) = self.classify(seq) {
+            counts.hits[r] += 1;
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Motif / primer search with IUPAC codes and mismatches, on both strands
+
+use crate::modules::revcomp;
+
+pub const POSITION_BINS: usize = 20;
+
+#[derive(Clone, PartialEq)]
+pub struct Motif {
+    pub name: String,
+    pub fwd: Vec<u8>,
+    pub rc: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct MotifResult {
+    pub file: String,
+    pub motif: String,
+    pub reads: u64, // reads searched
+    pub hits: u64, // reads with a match on either strand
+    pub fwd: u64,
+    pub rc: u64,
+    pub positions: [u64; POSITION_BINS], // relative start of the first match in the read
+}
+
+// one motif per line or comma separated, optionally named as "name=SEQUENCE"
+pub fn parse_motifs(text: &str) -> Vec<Motif> {
+    text.split(['\n', ','])
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .filter_map(|m| {
+            let (name, seq) = m.split_once('=').unwrap_or((m, m));
+            // RNA motifs are searched as DNA, so that U has a reverse complement
+            let seq: Vec<u8> = seq.trim().bytes().map(|b| b.to_ascii_uppercase()).map(|b| if b == b'U' { b'T' } else { b }).collect();
+            if seq.is_empty() || !seq.iter().all(|b| b"ACGTURYSWKMBDHVN".contains(b)) {
+                return None;
+            }
+            Some(Motif { name: name.trim().to_string(), rc: revcomp(&seq), fwd: seq })
+        })
+        .collect()
+}
+
+// does the base match the IUPAC code of the motif
+fn iupac_match(code: u8, base: u8) -> bool {
+    let base = base.to_ascii_uppercase();
+    match code {
+        b'A' | b'C' | b'G' => code == base,
+        b'T' => base == b'T' || base == b'U',
+        b'R' => matches!(base, b'A' | b'G'),
+        b'Y' => matches!(base, b'C' | b'T'),
+        b'S' => matches!(base, b'G' | b'C'),
+        b'W' => matches!(base, b'A' | b'T'),
+        b'K' => matches!(base, b'G' | b'T'),
+        b'M' => matches!(base, b'A' | b'C'),
+        b'B' => matches!(base, b'C' | b'G' | b'T'),
+        b'D' => matches!(base, b'A' | b'G' | b'T'),
+        b'H' => matches!(base, b'A' | b'C' | b'T'),
+        b'V' => matches!(base, b'A' | b'C' | b'G'),
+        _ => true, // N
+    }
+}
+
+// start of the first match with at most `mismatches` substitutions
+pub fn find(seq: &[u8], motif: &[u8], mismatches: usize) -> Option<usize> {
+    if motif.is_empty() || seq.len() < motif.len() {
+        return None;
+    }
+    (0..=seq.len() - motif.len()).find(|&start| {
+        let mut mm = 0;
+        for (i, &code) in motif.iter().enumerate() {
+            if !iupac_match(code, seq[start + i]) {
+                mm += 1;
+                if mm > mismatches {
+                    return false;
+                }
+            }
+        }
+        true
+    })
+}
+
+impl MotifResult {
+    pub fn new(file: &str, motif: &Motif) -> Self {
+        MotifResult {
+            file: file.to_string(),
+            motif: motif.name.clone(),
+            reads: 0,
+            hits: 0,
+            fwd: 0,
+            rc: 0,
+            positions: [0; POSITION_BINS],
+        }
+    }
+
+    pub fn add(&mut self, seq: &[u8], motif: &Motif, mismatches: usize) {
+        self.reads += 1;
+        let fwd = find(seq, &motif.fwd, mismatches);
+        let rc = find(seq, &motif.rc, mismatches);
+        if fwd.is_some() {
+            self.fwd += 1;
+        }
+        if rc.is_some() {
+            self.rc += 1;
+        }
+        if let Some(start) = fwd.into_iter().chain(rc).min() {
+            self.hits += 1;
+            let bin = start * POSITION_BINS / seq.len().max(1);
+            self.positions[bin.min(POSITION_BINS - 1)] += 1;
+        }
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.hits as f64 / self.reads.max(1) as f64 * 100.0
+    }
+
+    // sparkline of match positions along the read, same bars as the table histograms
+    pub fn position_histogram(&self) -> String {
+        let max = *self.positions.iter().max().unwrap_or(&0);
+        self.positions
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                let height = if max > 0 { count as f64 / max as f64 * 100.0 } else { 0.0 };
+                format!(
+                    r#"<div class="bar" style="height: {height}%;">
+                        <div class="tooltip">{start}-{end}% of read length:<br/>
+                        {count} reads</div>
+                    </div>"#,
+                    height = height,
+                    start = i * 100 / POSITION_BINS,
+                    end = (i + 1) * 100 / POSITION_BINS,
+                    count = count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
@@ -0,0 +1,288 @@
+// Bottom-k MinHash sketches of the k-mers in a file, for detecting swapped or duplicated samples
+
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use crate::kmers;
+
+pub const K: usize = 21;
+pub const SIZE: usize = 1000;
+
+// the SIZE smallest distinct canonical k-mer hashes, in ascending order
+#[derive(Clone, Default, PartialEq)]
+pub struct Sketch {
+    pub hashes: Vec<u64>,
+}
+
+impl Sketch {
+    pub fn add(&mut self, seq: &[u8]) {
+        kmers::canonical_hashes(seq, K, |_, hash| self.insert(hash));
+    }
+
+    // whether a hash would still make it into the sketch
+    fn wants(&self, hash: u64) -> bool {
+        self.hashes.len() < SIZE || hash < self.hashes[SIZE - 1]
+    }
+
+    fn insert(&mut self, hash: u64) {
+        if !self.wants(hash) {
+            return;
+        }
+        if let Err(pos) = self.hashes.binary_search(&hash) {
+            self.hashes.insert(pos, hash);
+            self.hashes.truncate(SIZE);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    // Jaccard estimate: shared hashes among the SIZE smallest of the union
+    pub fn jaccard(&self, other: &Sketch) -> f64 {
+        let (mut i, mut j, mut shared, mut seen) = (0, 0, 0, 0);
+        while seen < SIZE && i < self.hashes.len() && j < other.hashes.len() {
+            match self.hashes[i].cmp(&other.hashes[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    shared += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+            seen += 1;
+        }
+        seen += (self.hashes.len() - i + other.hashes.len() - j).min(SIZE - seen);
+        if seen == 0 {
+            0.0
+        } else {
+            shared as f64 / seen as f64
+        }
+    }
+}
+
+// Sketch of a read set: k-mers seen only once are mostly sequencing errors and would crowd out
+// the genome's k-mers, so like `mash -m 2` a hash only enters the sketch on its second
+// occurrence. First occurrences go into a Bloom filter; only hashes still small enough for
+// the sketch are looked up, so the filter stays sparse.
+pub struct ReadSketch {
+    pub sketch: Sketch,
+    seen: Vec<u64>, // Bloom filter bits
+}
+
+const BLOOM_BITS: u64 = 1 << 24;
+
+impl Default for ReadSketch {
+    fn default() -> Self {
+        ReadSketch { sketch: Sketch::default(), seen: vec![0; (BLOOM_BITS / 64) as usize] }
+    }
+}
+
+impl ReadSketch {
+    pub fn add(&mut self, seq: &[u8]) {
+        kmers::canonical_hashes(seq, K, |_, hash| {
+            if !self.sketch.wants(hash) {
+                return;
+            }
+            // three probes by double hashing
+            let step = hash.rotate_left(32) | 1;
+            let mut seen = true;
+            for i in 0..3u64 {
+                let bit = hash.wrapping_add(i.wrapping_mul(step)) % BLOOM_BITS;
+                let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+                seen &= self.seen[word] & mask != 0;
+                self.seen[word] |= mask;
+            }
+            if seen {
+                self.sketch.insert(hash);
+            }
+        });
+    }
+}
+
+// a sketch with the file it came from; saved sketches are compared like loaded files
+#[derive(Clone, PartialEq)]
+pub struct NamedSketch {
+    pub name: String,
+    pub reads: u64,
+    pub saved: bool,
+    pub sketch: Sketch,
+}
+
+pub fn to_json(sketches: &[NamedSketch]) -> String {
+    let entries: Vec<Value> = sketches
+        .iter()
+        .map(|s| json!({ "name": s.name, "reads": s.reads, "hashes": s.sketch.hashes }))
+        .collect();
+    json!({ "kmer": K, "size": SIZE, "sketches": entries }).to_string()
+}
+
+pub fn from_json(text: &str) -> Result<Vec<NamedSketch>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if value["kmer"].as_u64() != Some(K as u64) || value["size"].as_u64() != Some(SIZE as u64) {
+        return Err(format!("sketches must have k={} and size={}", K, SIZE));
+    }
+    let entries = value["sketches"].as_array().ok_or("no sketches found")?;
+    entries
+        .iter()
+        .map(|e| {
+            let name = e["name"].as_str().ok_or("sketch without name")?;
+            let mut hashes: Vec<u64> = e["hashes"]
+                .as_array()
+                .ok_or("sketch without hashes")?
+                .iter()
+                .filter_map(|h| h.as_u64())
+                .collect();
+            hashes.sort_unstable();
+            Ok(NamedSketch {
+                name: name.to_string(),
+                reads: e["reads"].as_u64().unwrap_or(0),
+                saved: true,
+                sketch: Sketch { hashes },
+            })
+        })
+        .collect()
+}
+
+pub fn similarity_matrix(sketches: &[NamedSketch]) -> Vec<Vec<f64>> {
+    sketches
+        .iter()
+        .map(|a| sketches.iter().map(|b| a.sketch.jaccard(&b.sketch)).collect())
+        .collect()
+}
+
+// leaf order of an average-linkage clustering, so that similar files end up next to each other
+pub fn cluster_order(matrix: &[Vec<f64>]) -> Vec<usize> {
+    let mut clusters: Vec<Vec<usize>> = (0..matrix.len()).map(|i| vec![i]).collect();
+    let linkage = |a: &[usize], b: &[usize]| {
+        let total: f64 = a.iter().flat_map(|&i| b.iter().map(move |&j| matrix[i][j])).sum();
+        total / (a.len() * b.len()) as f64
+    };
+    while clusters.len() > 1 {
+        let mut best = (0, 1, f64::MIN);
+        for i in 0..clusters.len() {
+            for j in i + 1..clusters.len() {
+                let s = linkage(&clusters[i], &clusters[j]);
+                if s > best.2 {
+                    best = (i, j, s);
+                }
+            }
+        }
+        let merged = clusters.remove(best.1);
+        clusters[best.0].extend(merged);
+    }
+    clusters.pop().unwrap_or_default()
+}
+
+// groups of files connected by a similarity of at least threshold
+pub fn similar_groups(matrix: &[Vec<f64>], threshold: f64) -> Vec<Vec<usize>> {
+    let n = matrix.len();
+    let mut group: Vec<usize> = (0..n).collect();
+    fn root(group: &mut [usize], i: usize) -> usize {
+        let mut i = i;
+        while group[i] != i {
+            group[i] = group[group[i]];
+            i = group[i];
+        }
+        i
+    }
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, &similarity) in row.iter().enumerate().skip(i + 1) {
+            if similarity >= threshold {
+                let (a, b) = (root(&mut group, i), root(&mut group, j));
+                group[a] = b;
+            }
+        }
+    }
+    let mut groups: std::collections::BTreeMap<usize, Vec<usize>> = std::collections::BTreeMap::new();
+    for i in 0..n {
+        let r = root(&mut group, i);
+        groups.entry(r).or_default().push(i);
+    }
+    groups.into_values().filter(|g| g.len() > 1).collect()
+}
+
+// reference sketches (e.g. one per genome) queried for containment in whole files
+#[derive(Clone, Default)]
+pub struct SketchDb {
+    pub refs: Vec<NamedSketch>,
+    hashes: HashSet<u64>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Match {
+    pub reference: String,
+    pub shared: usize,
+    pub size: usize,
+}
+
+impl Match {
+    // fraction of the reference sketch found among the k-mers of the file
+    pub fn containment(&self) -> f64 {
+        self.shared as f64 / self.size as f64
+    }
+
+    // Mash screen identity estimate
+    pub fn identity(&self) -> f64 {
+        self.containment().powf(1.0 / K as f64)
+    }
+}
+
+impl SketchDb {
+    pub fn add(&mut self, refs: Vec<NamedSketch>) {
+        for r in refs {
+            self.hashes.extend(r.sketch.hashes.iter().copied());
+            self.refs.push(r);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.refs.is_empty()
+    }
+
+    // collects the reference hashes present in seq
+    pub fn screen(&self, found: &mut HashSet<u64>, seq: &[u8]) {
+        kmers::canonical_hashes(seq, K, |_, hash| {
+            if self.hashes.contains(&hash) {
+                found.insert(hash);
+            }
+        });
+    }
+
+    pub fn top_matches(&self, found: &HashSet<u64>, n: usize) -> Vec<Match> {
+        let mut matches: Vec<Match> = self
+            .refs
+            .iter()
+            .filter(|r| !r.sketch.is_empty())
+            .map(|r| Match {
+                reference: r.name.clone(),
+                shared: r.sketch.hashes.iter().filter(|h| found.contains(h)).count(),
+                size: r.sketch.hashes.len(),
+            })
+            .filter(|m| m.shared > 0)
+            .collect();
+        matches.sort_by(|a, b| b.containment().total_cmp(&a.containment()));
+        matches.truncate(n);
+        matches
+    }
+}
+
+// all sketch files of a database folder
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_dir(dir: &std::path::Path) -> Result<Vec<NamedSketch>, String> {
+    let mut paths: Vec<std::path::PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    let mut sketches = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        sketches.extend(from_json(&text).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    Ok(sketches)
+}
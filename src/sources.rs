@@ -0,0 +1,210 @@
+// Where the bytes of a loaded file come from, and discovery of sequence files in folders
+
+use std::sync::Arc;
+
+use bio::io::{fasta, fastq};
+use dioxus::prelude::dioxus_elements::FileEngine;
+
+#[derive(Clone)]
+pub enum FileSource {
+    // files picked with the file input or dropped on the window
+    Engine(Arc<dyn FileEngine>),
+    // files found on disk, the file name is the full path
+    #[cfg(not(target_arch = "wasm32"))]
+    Disk,
+    // files created by the app, e.g. filtered reads in the browser
+    Memory(Arc<Vec<u8>>),
+    // files found in folders dropped in the browser, the file name is the path in the drop
+    #[cfg(target_arch = "wasm32")]
+    Dropped(Arc<Vec<(String, web_sys::File)>>),
+}
+
+impl FileSource {
+    pub async fn read(&self, name: &str) -> Option<Vec<u8>> {
+        match self {
+            FileSource::Engine(engine) => engine.read_file(name).await,
+            #[cfg(not(target_arch = "wasm32"))]
+            FileSource::Disk => tokio::fs::read(name).await.ok(),
+            FileSource::Memory(bytes) => Some(bytes.to_vec()),
+            #[cfg(target_arch = "wasm32")]
+            FileSource::Dropped(files) => {
+                let (_, file) = files.iter().find(|(path, _)| path == name)?;
+                let buffer = wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await.ok()?;
+                Some(js_sys::Uint8Array::new(&buffer).to_vec())
+            }
+        }
+    }
+}
+
+const FASTQ_EXT: [&str; 2] = [".fastq", ".fq"];
+const FASTA_EXT: [&str; 4] = [".fasta", ".fa", ".fna", ".fas"];
+
+fn strip_gz(name: &str) -> &str {
+    name.strip_suffix(".gz").unwrap_or(name)
+}
+
+pub fn is_fasta(name: &str) -> bool {
+    let name = strip_gz(name).to_ascii_lowercase();
+    FASTA_EXT.iter().any(|ext| name.ends_with(ext))
+}
+
+// "run1/reads.fastq.gz" -> "reads"
+pub fn file_stem(name: &str) -> String {
+    let basename = std::path::Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let stem = strip_gz(&basename);
+    FASTQ_EXT
+        .iter()
+        .chain(FASTA_EXT.iter())
+        .find_map(|ext| stem.strip_suffix(ext))
+        .unwrap_or(stem)
+        .to_string()
+}
+
+pub fn is_sequence_file(name: &str) -> bool {
+    let lower = strip_gz(name).to_ascii_lowercase();
+    FASTQ_EXT.iter().any(|ext| lower.ends_with(ext)) || is_fasta(name)
+}
+
+// FASTA records are read as FASTQ records without qualities, so that one code path handles both
+pub fn records(reader: Box<dyn std::io::Read + Send>, fasta: bool) -> Box<dyn Iterator<Item = fastq::Record>> {
+    if fasta {
+        Box::new(
+            fasta::Reader::new(reader)
+                .records()
+                .map_while(Result::ok)
+                .map(|rec| fastq::Record::with_attrs(rec.id(), rec.desc(), rec.seq(), &[])),
+        )
+    } else {
+        Box::new(fastq::Reader::new(reader).records().map_while(Result::ok))
+    }
+}
+
+// shell style wildcard match, '*' matches any run of characters and '?' a single one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((spi, sti)) = star {
+            pi = spi + 1;
+            ti = sti + 1;
+            star = Some((spi, sti + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+// include and exclude are comma separated globs, matched against the file name
+// (or the whole path when the glob contains a '/'); an empty include keeps everything
+pub fn filter_name(name: &str, include: &str, exclude: &str) -> bool {
+    let basename = std::path::Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let matches = |globs: &str| {
+        globs
+            .split(',')
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .any(|g| glob_match(g, if g.contains('/') { name } else { &basename }))
+    };
+    let included = include.trim().is_empty() || matches(include);
+    is_sequence_file(name) && included && !matches(exclude)
+}
+
+// all sequence files below dir, in natural order; symlinked folders are followed,
+// each folder once, so that link cycles end
+#[cfg(not(target_arch = "wasm32"))]
+pub fn discover(dir: &std::path::Path) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(canonical) = std::fs::canonicalize(&current) else {
+            continue;
+        };
+        if !visited.insert(canonical) {
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if is_sequence_file(&path.to_string_lossy()) {
+                found.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    found.sort_by(|a, b| natord::compare(a, b));
+    found
+}
+
+// entries of a drop in the browser, read while the drop is handled as they are gone afterwards
+#[cfg(target_arch = "wasm32")]
+pub fn dropped_entries(event: &web_sys::DragEvent) -> Vec<web_sys::FileSystemEntry> {
+    let Some(items) = event.data_transfer().map(|data| data.items()) else {
+        return Vec::new();
+    };
+    (0..items.length()).filter_map(|i| items.get(i)?.webkit_get_as_entry().ok().flatten()).collect()
+}
+
+// all sequence files among dropped entries and below dropped folders, in natural order
+#[cfg(target_arch = "wasm32")]
+pub async fn walk_entries(entries: Vec<web_sys::FileSystemEntry>) -> Vec<(String, web_sys::File)> {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+    // the entry API reports through callbacks, a failed call rejects instead of hanging
+    let callback = |call: &dyn Fn(&js_sys::Function, &js_sys::Function) -> Result<(), wasm_bindgen::JsValue>| {
+        JsFuture::from(js_sys::Promise::new(&mut |resolve, reject| {
+            if let Err(e) = call(&resolve, &reject) {
+                let _ = reject.call1(&wasm_bindgen::JsValue::NULL, &e);
+            }
+        }))
+    };
+    let mut found = Vec::new();
+    let mut stack = entries;
+    while let Some(entry) = stack.pop() {
+        let path = entry.full_path().trim_start_matches('/').to_string();
+        if entry.is_directory() {
+            let reader = entry.unchecked_into::<web_sys::FileSystemDirectoryEntry>().create_reader();
+            // readEntries returns the folder in batches, an empty one at the end
+            loop {
+                let Ok(batch) = callback(&|resolve, reject| reader.read_entries_with_callback_and_callback(resolve, reject)).await else {
+                    break;
+                };
+                let batch: js_sys::Array = batch.unchecked_into();
+                if batch.length() == 0 {
+                    break;
+                }
+                stack.extend(batch.iter().map(|e| e.unchecked_into()));
+            }
+        } else if is_sequence_file(&path) {
+            let entry: &web_sys::FileSystemFileEntry = entry.unchecked_ref();
+            if let Ok(file) = callback(&|resolve, reject| {
+                entry.file_with_callback_and_callback(resolve, reject);
+                Ok(())
+            })
+            .await
+            {
+                found.push((path, file.unchecked_into()));
+            }
+        }
+    }
+    found.sort_by(|a, b| natord::compare(&a.0, &b.0));
+    found
+}
@@ -0,0 +1,181 @@
+// Per-file statistics, accumulated one record at a time
+
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use bio::io::fastq;
+
+use crate::metrics::Metric;
+use crate::{density, groups, headers, modules, positions, yields, UploadedFile};
+
+// the platform is the most common one among the first reads of a file
+const PLATFORM_READS: u64 = 100;
+
+pub struct FileStats {
+    name: String,
+    basename: String,
+    is_mate: bool, // R1/R2 by file name
+    pub nreads: u64,
+    pub nbases: u64,
+    gcbases: u64,
+    qual20: i64,
+    qual30: i64,
+    len_vector: Vec<i64>,
+    qual_vector: Vec<u8>,
+    qhash: BTreeMap<u8, i64>,
+    index_counts: BTreeMap<String, u64>,
+    platform: &'static str,
+    platform_votes: BTreeMap<&'static str, u64>,
+    hifi: headers::HifiStats,
+    id_hashes: Vec<u64>,
+    interleaved: bool,
+    mate_gc_bases: [u64; 2],
+    mate_q_hash: [BTreeMap<u8, i64>; 2],
+    seq_sample: Vec<Vec<u8>>,
+    short_reads: bool, // no read longer than groups::INSERT_READ_LENGTH so far
+    density: density::Density,
+    yields: yields::YieldMatrix,
+    positions: positions::Positions,
+}
+
+impl FileStats {
+    pub fn new(name: &str) -> Self {
+        let basename = Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.to_string());
+        FileStats {
+            name: name.to_string(),
+            is_mate: headers::mate_from_filename(&basename).is_some(),
+            basename,
+            nreads: 0,
+            nbases: 0,
+            gcbases: 0,
+            qual20: 0,
+            qual30: 0,
+            len_vector: Vec::new(),
+            qual_vector: Vec::new(),
+            qhash: BTreeMap::new(),
+            index_counts: BTreeMap::new(),
+            platform: "unknown",
+            platform_votes: BTreeMap::new(),
+            hifi: headers::HifiStats::default(),
+            id_hashes: Vec::new(),
+            interleaved: true,
+            mate_gc_bases: [0; 2],
+            mate_q_hash: Default::default(),
+            seq_sample: Vec::new(),
+            short_reads: true,
+            density: density::Density::default(),
+            yields: yields::YieldMatrix::default(),
+            positions: positions::Positions::default(),
+        }
+    }
+
+    pub fn add(&mut self, rec: &fastq::Record) {
+        if self.nreads < PLATFORM_READS {
+            *self.platform_votes.entry(headers::detect_platform(rec.id(), rec.desc())).or_insert(0) += 1;
+            self.platform = self
+                .platform_votes
+                .iter()
+                .filter(|(&platform, _)| platform != "unknown")
+                .max_by_key(|(_, &n)| n)
+                .map_or("unknown", |(&platform, _)| platform);
+        }
+        let mate = (self.nreads % 2) as usize;
+        if self.interleaved && headers::mate_number(rec.id(), rec.desc()) != Some(mate as u8 + 1) {
+            self.interleaved = false;
+        }
+        self.nreads += 1;
+        let gc = modules::get_gc_bases(rec.seq());
+        self.gcbases += gc;
+        self.nbases += rec.seq().len() as u64;
+        self.qual20 += modules::get_qual_bases(rec.qual(), 53); // 33 offset
+        self.qual30 += modules::get_qual_bases(rec.qual(), 63); // 33 offset
+        self.len_vector.push(rec.seq().len() as i64);
+        let qscore = modules::qscore_mean(rec.qual());
+        self.qual_vector.push(qscore);
+        if !rec.qual().is_empty() {
+            self.density.add(rec.seq().len(), qscore);
+            self.yields.add(rec.seq().len(), qscore);
+        }
+        for &q in rec.qual() {
+            *self.qhash.entry(q).or_insert(0) += 1; // Count occurrences of each quality score
+        }
+        if self.interleaved {
+            self.mate_gc_bases[mate] += gc;
+            for &q in rec.qual() {
+                *self.mate_q_hash[mate].entry(q).or_insert(0) += 1;
+            }
+        }
+        if self.short_reads && rec.seq().len() > groups::INSERT_READ_LENGTH {
+            self.short_reads = false;
+            self.seq_sample = Vec::new();
+        }
+        if self.short_reads && (self.is_mate || self.interleaved) && self.seq_sample.len() < groups::INSERT_SAMPLE {
+            self.seq_sample.push(rec.seq().to_vec());
+        }
+        self.positions.add(rec.seq(), rec.qual());
+        if self.is_mate {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            headers::read_id_key(rec.id()).hash(&mut hasher);
+            self.id_hashes.push(hasher.finish());
+        }
+        // while the platform is open every read is parsed, finish drops this for other platforms
+        if self.nreads <= PLATFORM_READS || self.platform == "PacBio" {
+            self.hifi.add(rec.id(), rec.desc());
+        }
+        if let Some(index) = headers::illumina_index(rec.desc()) {
+            match self.index_counts.get_mut(index) {
+                Some(n) => *n += 1,
+                None => {
+                    self.index_counts.insert(index.to_string(), 1);
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> UploadedFile {
+        let interleaved = self.interleaved && self.nreads >= 2 && self.nreads.is_multiple_of(2);
+        // the vectors keep the read order, mates of interleaved files are split by record parity
+        // and subsampling picks reads by their index
+        let n50 = if self.len_vector.is_empty() { 0 } else { modules::get_nx(&mut self.len_vector.clone(), 0.5) };
+        let median_qscore = if self.qual_vector.is_empty() { 0 } else { modules::median(&mut self.qual_vector.clone()) };
+
+        let qual_total = self.qhash.values().sum::<i64>() as f64; // bases with qualities
+        UploadedFile {
+            name: self.name,
+            basename: self.basename,
+            reads: self.nreads,
+            bases: self.nbases,
+            q20: Metric::percent(self.qual20 as f64, qual_total),
+            q30: Metric::percent(self.qual30 as f64, qual_total),
+            nx: n50 as u64,
+            gc: Metric::percent(self.gcbases as f64, self.nbases as f64),
+            m_qscore: median_qscore,
+            q_vector: self.qual_vector,
+            l_vector: self.len_vector,
+            q_hash: self.qhash,
+            index_counts: self.index_counts,
+            platform: self.platform.to_string(),
+            hifi: if self.platform == "PacBio" { self.hifi } else { Default::default() },
+            gc_bases: self.gcbases,
+            id_hashes: self.id_hashes,
+            interleaved,
+            mate_gc_bases: self.mate_gc_bases,
+            mate_q_hash: self.mate_q_hash,
+            // mates of interleaved files are both here, R1/R2 files wait for the other file
+            inserts: if interleaved {
+                groups::insert_sizes(self.seq_sample.chunks_exact(2).map(|pair| (&pair[0], &pair[1])))
+            } else {
+                Vec::new()
+            },
+            seq_sample: if self.is_mate && !interleaved { self.seq_sample } else { Vec::new() },
+            screen: Default::default(),
+            density: self.density,
+            yields: self.yields,
+            positions: self.positions,
+        }
+    }
+}
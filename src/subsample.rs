@@ -0,0 +1,134 @@
+// Downsampling to a number of reads, bases or a genome coverage
+
+use bio::io::fastq;
+
+use crate::UploadedFile;
+
+#[derive(Clone, PartialEq)]
+pub struct Subsample {
+    pub target: String, // "reads", "bases" or "coverage"
+    pub amount: f64,
+    pub genome_size: u64,
+    pub seed: u64,
+    pub prefer: String, // "random", "longest" or "quality"
+}
+
+impl Default for Subsample {
+    fn default() -> Self {
+        Subsample {
+            target: "reads".to_string(),
+            amount: 10000.0,
+            genome_size: 5_000_000,
+            seed: 42,
+            prefer: "random".to_string(),
+        }
+    }
+}
+
+impl Subsample {
+    pub fn describe(&self) -> String {
+        let target = match self.target.as_str() {
+            "bases" => format!("{} bases", self.amount),
+            "coverage" => format!("{}x of {} bp", self.amount, self.genome_size),
+            _ => format!("{} reads", self.amount),
+        };
+        format!("subsample to {} ({}, seed {})", target, self.prefer, self.seed)
+    }
+
+    // which reads of this file to keep, decided up front from its statistics; reads are picked
+    // in random, length or quality order until the target is reached, counting actual bases
+    pub fn plan(&self, f: &UploadedFile) -> SamplePlan {
+        let target_bases = match self.target.as_str() {
+            "bases" => Some(self.amount as u64),
+            "coverage" => Some((self.amount * self.genome_size as f64) as u64),
+            _ => None,
+        };
+        if f.reads == 0 || target_bases.unwrap_or(0) >= f.bases || (target_bases.is_none() && self.amount as u64 >= f.reads) {
+            return SamplePlan::All;
+        }
+        let n = f.l_vector.len();
+        if target_bases.is_none() && self.prefer == "random" {
+            return SamplePlan::Keep { keep: reservoir(n, self.amount as usize, self.seed), index: 0 };
+        }
+
+        // read indices in the order they are picked, ties in read order
+        let mut order: Vec<usize> = (0..n).collect();
+        match self.prefer.as_str() {
+            "longest" => order.sort_by_key(|&i| std::cmp::Reverse(f.l_vector[i])),
+            "quality" => order.sort_by_key(|&i| std::cmp::Reverse(f.q_vector.get(i).copied().unwrap_or(0))),
+            _ => shuffle(&mut order, self.seed),
+        }
+        let picked = match target_bases {
+            Some(bases) => {
+                let mut sum = 0;
+                order.iter().take_while(|&&i| {
+                    let before = sum;
+                    sum += f.l_vector[i] as u64;
+                    before < bases
+                }).count()
+            }
+            None => self.amount as usize,
+        };
+        let mut keep = vec![false; n];
+        for &i in order.iter().take(picked) {
+            keep[i] = true;
+        }
+        SamplePlan::Keep { keep, index: 0 }
+    }
+}
+
+pub enum SamplePlan {
+    All,
+    Keep { keep: Vec<bool>, index: usize }, // by record index
+}
+
+impl SamplePlan {
+    pub fn select(&mut self, rec: &fastq::Record) -> Option<fastq::Record> {
+        let keep = match self {
+            SamplePlan::All => true,
+            SamplePlan::Keep { keep, index } => {
+                *index += 1;
+                keep.get(*index - 1).copied().unwrap_or(false)
+            }
+        };
+        keep.then(|| rec.clone())
+    }
+}
+
+// reproducible Fisher-Yates shuffle
+fn shuffle(items: &mut [usize], seed: u64) {
+    let mut rng = SplitMix64(seed);
+    for i in (1..items.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+// reproducible reservoir sample (algorithm R) of k out of n record indices
+pub fn reservoir(n: usize, k: usize, seed: u64) -> Vec<bool> {
+    let mut rng = SplitMix64(seed);
+    let mut sample: Vec<usize> = (0..k.min(n)).collect();
+    for i in k..n {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        if j < k {
+            sample[j] = i;
+        }
+    }
+    let mut keep = vec![false; n];
+    for i in sample {
+        keep[i] = true;
+    }
+    keep
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
@@ -0,0 +1,70 @@
+// Bases in reads with length >= L and mean quality >= Q, for a fixed grid of L and Q
+
+use human_repr::HumanCount;
+
+pub const LENGTHS: [usize; 5] = [1_000, 5_000, 10_000, 20_000, 50_000];
+pub const QSCORES: [u8; 5] = [7, 10, 12, 15, 20];
+
+// bases[length row][qscore column]
+#[derive(Clone, Default, PartialEq)]
+pub struct YieldMatrix {
+    bases: [[u64; QSCORES.len()]; LENGTHS.len()],
+}
+
+impl YieldMatrix {
+    pub fn add(&mut self, length: usize, qscore: u8) {
+        for (row, &min_length) in self.bases.iter_mut().zip(LENGTHS.iter()) {
+            if length < min_length {
+                break;
+            }
+            for (cell, &min_q) in row.iter_mut().zip(QSCORES.iter()) {
+                if qscore >= min_q {
+                    *cell += length as u64;
+                }
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &YieldMatrix) {
+        for (row, other_row) in self.bases.iter_mut().zip(other.bases.iter()) {
+            for (cell, other_cell) in row.iter_mut().zip(other_row.iter()) {
+                *cell += other_cell;
+            }
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<table class='index-table yield-table'>\n<thead><tr><th>Bases</th>");
+        for q in QSCORES {
+            html.push_str(&format!("<th>Q ≥ {}</th>", q));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for (row, length) in self.bases.iter().zip(LENGTHS.iter()) {
+            html.push_str(&format!("<tr><td>≥ {} bp</td>", length.human_count_bare()));
+            for bases in row {
+                html.push_str(&format!("<td>{}</td>", bases.human_count_bare()));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        html
+    }
+
+    // tab separated, for the command line
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("length\\Q");
+        for q in QSCORES {
+            text.push_str(&format!("\t>={}", q));
+        }
+        text.push('\n');
+        for (row, length) in self.bases.iter().zip(LENGTHS.iter()) {
+            text.push_str(&format!(">={}", length));
+            for bases in row {
+                text.push_str(&format!("\t{}", bases));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}